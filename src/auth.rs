@@ -0,0 +1,193 @@
+use serde::Deserialize;
+
+use crate::error::{Error, Result};
+
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+
+/// env vars holding a token, in order of precedence
+/// `GITHUB_ACCESS` is what this tool read before the others were supported
+pub const TOKEN_ENV_VARS: &[&str] = &["GITHUB_TOKEN", "GH_TOKEN", "GITHUB_ACCESS"];
+
+/// host the gh cli stores api.github.com tokens under
+const GH_HOST: &str = "github.com";
+
+/// a github access token and where it came from
+#[derive(Clone)]
+pub struct Token {
+    pub value: String,
+    pub source: TokenSource,
+}
+
+// keep tokens out of debug output and logs
+impl fmt::Debug for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Token")
+            .field("value", &"***")
+            .field("source", &self.source)
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenSource {
+    TokenFile(PathBuf),
+    Env(&'static str),
+    GhCli(PathBuf),
+    ConfigFile(PathBuf),
+}
+
+impl fmt::Display for TokenSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenSource::TokenFile(path) => write!(f, "token file {}", path.display()),
+            TokenSource::Env(var) => write!(f, "${}", var),
+            TokenSource::GhCli(path) => write!(f, "gh cli {}", path.display()),
+            TokenSource::ConfigFile(path) => write!(f, "config file {}", path.display()),
+        }
+    }
+}
+
+/// where to look for a token
+#[derive(Debug, Clone, Default)]
+pub struct AuthOptions {
+    /// file holding nothing but the token, has to exist when given
+    pub token_file: Option<PathBuf>,
+    /// skip every source and make anonymous requests
+    pub anonymous: bool,
+}
+
+/// settings read from `config.toml`
+#[derive(Debug, Default, Deserialize)]
+struct Config {
+    token: Option<String>,
+}
+
+/// find a token, trying in order
+///
+/// 1. `--token-file`
+/// 2. `$GITHUB_TOKEN`, `$GH_TOKEN`, `$GITHUB_ACCESS`
+/// 3. the gh cli's `hosts.yml` in `$GH_CONFIG_DIR` or `~/.config/gh`
+/// 4. `token` in `$XDG_CONFIG_HOME/starred-repos/config.toml`
+///
+/// returns None when anonymous or no source has a token,
+/// github serves public star lists without one at a lower rate limit
+pub fn resolve_token(options: &AuthOptions) -> Result<Option<Token>> {
+    if options.anonymous {
+        return Ok(None);
+    }
+
+    if let Some(path) = &options.token_file {
+        let value = fs::read_to_string(path)
+            .map_err(|err| Error::io(format!("Could not read token file {}", path.display()), err))?;
+        let value = value.trim().to_string();
+        if value.is_empty() {
+            return Err(Error::invalid(format!("Token file {} is empty", path.display())));
+        }
+        return Ok(Some(Token {
+            value,
+            source: TokenSource::TokenFile(path.clone()),
+        }));
+    }
+
+    for var in TOKEN_ENV_VARS {
+        if let Some(value) = env::var(var).ok().filter(|value| !value.trim().is_empty()) {
+            return Ok(Some(Token {
+                value: value.trim().to_string(),
+                source: TokenSource::Env(var),
+            }));
+        }
+    }
+
+    if let Some(path) = gh_hosts_path() {
+        if let Some(value) = fs::read_to_string(&path)
+            .ok()
+            .and_then(|hosts| parse_gh_hosts(&hosts, GH_HOST))
+        {
+            return Ok(Some(Token {
+                value,
+                source: TokenSource::GhCli(path),
+            }));
+        }
+    }
+
+    if let Some(path) = config_path().filter(|path| path.is_file()) {
+        let config = fs::read_to_string(&path)
+            .map_err(|err| Error::io(format!("Could not read config file {}", path.display()), err))?;
+        let config: Config = toml::from_str(&config)
+            .map_err(|err| Error::parse(format!("Could not parse config file {}", path.display()), err))?;
+        if let Some(value) = config.token.filter(|value| !value.trim().is_empty()) {
+            return Ok(Some(Token {
+                value: value.trim().to_string(),
+                source: TokenSource::ConfigFile(path),
+            }));
+        }
+    }
+
+    Ok(None)
+}
+
+/// `config.toml` of this tool
+pub fn config_path() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(dirs::config_dir)?;
+    Some(base.join("starred-repos").join("config.toml"))
+}
+
+/// `hosts.yml` of the gh cli, which uses `~/.config` on every unix
+fn gh_hosts_path() -> Option<PathBuf> {
+    let dir = match env::var_os("GH_CONFIG_DIR").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .or_else(|| dirs::home_dir().map(|home| home.join(".config")))?
+            .join("gh"),
+    };
+    Some(dir.join("hosts.yml"))
+}
+
+/// get the `oauth_token` of host from the gh cli's `hosts.yml`
+///
+/// only understands the plain mapping gh writes, preferring the token of the
+/// active account over those listed under `users:`. gh versions storing the
+/// token in the system keyring leave it out of the file entirely
+pub fn parse_gh_hosts(hosts: &str, host: &str) -> Option<String> {
+    let mut in_host = false;
+    let mut best: Option<(usize, String)> = None;
+
+    for line in hosts.lines() {
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+        let (key, value) = match line.trim().split_once(':') {
+            Some((key, value)) => (unquote(key.trim()), unquote(value.trim())),
+            None => continue,
+        };
+
+        if indent == 0 {
+            in_host = key == host;
+        } else if in_host
+            && key == "oauth_token"
+            && !value.is_empty()
+            && best.as_ref().is_none_or(|(depth, _)| indent < *depth)
+        {
+            best = Some((indent, value.to_string()));
+        }
+    }
+
+    best.map(|(_, token)| token)
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+        .unwrap_or(value)
+}
@@ -0,0 +1,355 @@
+use chrono::{DateTime, Utc};
+
+use serde::{Deserialize, Serialize};
+
+use sha2::{Digest, Sha256};
+
+use crate::error::{Error, Result};
+use crate::repo::{parse_starred, Repo};
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// layout version of cache entries
+///
+/// bump whenever `CacheEntry` or `Repo` change in a way older entries
+/// no longer deserialize into, entries of older versions are discarded
+/// and those of newer ones left to the binary that wrote them
+pub const CACHE_VERSION: u32 = 2;
+
+/// a cached starred list with what is needed to revalidate it
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CacheEntry {
+    /// `CACHE_VERSION` when written
+    pub version: u32,
+    /// whose starred list this is
+    pub user: String,
+    /// when the list was fetched or last revalidated
+    pub fetched_at: DateTime<Utc>,
+    /// how to revalidate each page, in order
+    pub pages: Vec<CachedPage>,
+    /// every page, merged and parsed
+    pub repos: Vec<Repo>,
+}
+
+/// one page of a cached list
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CachedPage {
+    /// where the page was fetched from
+    pub url: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    /// how many of the entry's repos came from this page
+    pub count: usize,
+}
+
+/// entry layout before versioning, holding the raw api response
+#[derive(Deserialize)]
+struct UnversionedEntry {
+    user: String,
+    fetched_at: DateTime<Utc>,
+    items: Vec<serde_json::Value>,
+}
+
+/// just the version of an entry, to tell how to read the rest
+#[derive(Deserialize)]
+struct Version {
+    version: Option<u32>,
+}
+
+impl CacheEntry {
+    /// read an entry of the current version, migrating unversioned ones
+    /// returns None for entries of other versions or ones that fail to parse
+    ///
+    /// unversioned entries only kept the validators of the first page,
+    /// so they lose them and are fetched again in full once stale
+    fn parse(cached: &str) -> Option<Self> {
+        match serde_json::from_str::<Version>(cached).ok()?.version {
+            Some(CACHE_VERSION) => serde_json::from_str(cached).ok(),
+            Some(_) => None,
+            None => {
+                let old: UnversionedEntry = serde_json::from_str(cached).ok()?;
+                Some(CacheEntry {
+                    version: CACHE_VERSION,
+                    user: old.user,
+                    fetched_at: old.fetched_at,
+                    pages: Vec::new(),
+                    repos: parse_starred(old.items).ok()?,
+                })
+            }
+        }
+    }
+
+    /// whether cached was written by a newer version of the cache layout
+    fn is_newer(cached: &str) -> bool {
+        serde_json::from_str::<Version>(cached)
+            .is_ok_and(|cached| cached.version.is_some_and(|version| version > CACHE_VERSION))
+    }
+
+    pub fn age(&self) -> Duration {
+        (Utc::now() - self.fetched_at).to_std().unwrap_or_default()
+    }
+
+    /// whether the entry can be used without asking github
+    pub fn is_fresh(&self, max_age: Duration) -> bool {
+        self.age() <= max_age
+    }
+}
+
+/// kinds of lists a key can be for, the first part of its file name
+const KEY_PREFIXES: &[&str] = &["starred", "org", "owned", "search", "list"];
+
+/// key of a cache entry, made of the kind of list, a validated login where
+/// there is one and a hash of the request
+///
+/// only ever contains ascii alphanumerics and `-`, so it is safe as a file name
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey(String);
+
+impl CacheKey {
+    /// key for the starred list of `user` requested with `params`,
+    /// e.g. the page size and media type, so differently shaped responses never mix
+    pub fn starred(user: &str, params: &[(&str, &str)]) -> Result<Self> {
+        validate_login(user)?;
+        let user = user.to_lowercase();
+        CacheKey::new(&format!("starred-{}", user), &format!("users/{}/starred", user), params)
+    }
+
+    /// key for the list at api path requested with params, named `{prefix}-{hash}`
+    ///
+    /// prefix has to start with one of `KEY_PREFIXES` and be made of
+    /// lowercase ascii alphanumerics and `-`, e.g. `org-rust-lang`
+    pub fn new(prefix: &str, path: &str, params: &[(&str, &str)]) -> Result<Self> {
+        let valid = KEY_PREFIXES.iter().any(|kind| prefix.split('-').next() == Some(kind))
+            && prefix.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+        if !valid {
+            return Err(Error::invalid(format!("{:?} is not a valid cache key prefix", prefix)));
+        }
+
+        let mut hasher = Sha256::new();
+        hasher.update(path);
+        for (name, value) in params {
+            hasher.update(format!("\n{}={}", name, value));
+        }
+        let hash: String = hasher.finalize()[..8]
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+
+        Ok(CacheKey(format!("{}-{}", prefix, hash)))
+    }
+
+    /// key stored under file name, None for names no key produces
+    fn from_file_name(name: &str) -> Option<Self> {
+        (KEY_PREFIXES.iter().any(|kind| name.starts_with(&format!("{}-", kind)))
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'))
+        .then(|| CacheKey(name.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// summary of a cached entry
+#[derive(Debug, Clone)]
+pub struct EntryInfo {
+    pub key: CacheKey,
+    pub user: String,
+    pub fetched_at: DateTime<Utc>,
+    /// bytes taken up by the entry
+    pub size: u64,
+    /// number of repos in the entry
+    pub count: usize,
+}
+
+impl EntryInfo {
+    pub fn age(&self) -> Duration {
+        (Utc::now() - self.fetched_at).to_std().unwrap_or_default()
+    }
+}
+
+/// check `login` is a possible github user or organization name:
+/// 1 to 39 ascii alphanumerics or hyphens, not starting with a hyphen
+pub fn validate_login(login: &str) -> Result<()> {
+    let valid = !login.is_empty()
+        && login.len() <= 39
+        && !login.starts_with('-')
+        && login.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+    if !valid {
+        return Err(Error::invalid(format!("{:?} is not a valid GitHub login", login)));
+    }
+    Ok(())
+}
+
+/// storage for starred lists
+pub trait Cache {
+    /// get cached entry for key
+    /// returns None on cache miss or an unreadable entry
+    fn get(&self, key: &CacheKey) -> Option<CacheEntry>;
+
+    /// store entry for key
+    fn put(&self, key: &CacheKey, entry: &CacheEntry) -> Result<()>;
+
+    /// remove the entry for key, a missing entry is not an error
+    fn remove(&self, key: &CacheKey) -> Result<()>;
+
+    /// every readable entry
+    fn entries(&self) -> Result<Vec<EntryInfo>>;
+
+    /// remove every cached entry, returns how many were removed
+    fn clear(&self) -> Result<usize>;
+
+    /// remove every entry for user, returns how many were removed
+    fn clear_user(&self, user: &str) -> Result<usize> {
+        remove_where(self, |entry| entry.user.eq_ignore_ascii_case(user))
+    }
+
+    /// remove entries fetched longer than `older_than` ago, returns how many were removed
+    fn prune(&self, older_than: Duration) -> Result<usize> {
+        remove_where(self, |entry| entry.age() > older_than)
+    }
+}
+
+/// remove entries matching predicate, returns how many were removed
+fn remove_where<C>(cache: &C, predicate: impl Fn(&EntryInfo) -> bool) -> Result<usize>
+where
+    C: Cache + ?Sized,
+{
+    let mut removed = 0;
+    for entry in cache.entries()?.iter().filter(|entry| predicate(entry)) {
+        cache.remove(&entry.key)?;
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+/// cache storing one json file per key in a directory
+pub struct FileCache {
+    dir: PathBuf,
+}
+
+/// env var overriding the cache dir
+pub const CACHE_DIR_ENV: &str = "STARRED_REPOS_CACHE_DIR";
+
+impl FileCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        FileCache { dir: dir.into() }
+    }
+
+    /// cache in `$STARRED_REPOS_CACHE_DIR`, else `$XDG_CACHE_HOME/starred-repos`,
+    /// else the platform cache dir (`~/.cache/starred-repos` on linux)
+    pub fn default_dir() -> Result<PathBuf> {
+        if let Some(dir) = env::var_os(CACHE_DIR_ENV).filter(|dir| !dir.is_empty()) {
+            return Ok(PathBuf::from(dir));
+        }
+        let base = env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .or_else(dirs::cache_dir)
+            .ok_or_else(|| {
+                Error::invalid("Could not find a cache directory, set XDG_CACHE_HOME or use --cache-dir")
+            })?;
+        Ok(base.join("starred-repos"))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+impl Cache for FileCache {
+    /// corrupt entries and those of older versions are removed,
+    /// ones of newer versions are kept for the binary that wrote them
+    fn get(&self, key: &CacheKey) -> Option<CacheEntry> {
+        let path = self.dir.join(key.as_str());
+        let cached = fs::read_to_string(&path).ok()?;
+        let entry = CacheEntry::parse(&cached);
+        if entry.is_none() && !CacheEntry::is_newer(&cached) {
+            let _ = fs::remove_file(&path);
+        }
+        entry
+    }
+
+    fn put(&self, key: &CacheKey, entry: &CacheEntry) -> Result<()> {
+        fs::create_dir_all(&self.dir)
+            .map_err(|err| Error::io(format!("Could not create cache dir {}", self.dir.display()), err))?;
+        let path = self.dir.join(key.as_str());
+        let json = serde_json::to_string(entry)
+            .map_err(|err| Error::parse("Could not serialize cache entry", err))?;
+        fs::write(&path, json)
+            .map_err(|err| Error::io(format!("Could not write cache entry {}", path.display()), err))?;
+        Ok(())
+    }
+
+    fn remove(&self, key: &CacheKey) -> Result<()> {
+        let path = self.dir.join(key.as_str());
+        match fs::remove_file(&path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(Error::io(
+                format!("Could not remove cache entry {}", path.display()),
+                err,
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn entries(&self) -> Result<Vec<EntryInfo>> {
+        let dir = match fs::read_dir(&self.dir) {
+            Ok(dir) => dir,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => {
+                return Err(Error::io(format!("Could not read cache dir {}", self.dir.display()), err))
+            }
+        };
+
+        let mut entries = Vec::new();
+        for file in dir.flatten() {
+            let key = match file.file_name().to_str().and_then(CacheKey::from_file_name) {
+                Some(key) => key,
+                None => continue,
+            };
+            let size = file.metadata().map(|meta| meta.len()).unwrap_or_default();
+            // unlike get, listing never removes anything
+            let entry = fs::read_to_string(file.path()).ok().and_then(|cached| CacheEntry::parse(&cached));
+            if let Some(entry) = entry {
+                entries.push(EntryInfo {
+                    key,
+                    user: entry.user,
+                    fetched_at: entry.fetched_at,
+                    size,
+                    count: entry.repos.len(),
+                });
+            }
+        }
+        entries.sort_by(|a, b| a.user.cmp(&b.user).then(b.fetched_at.cmp(&a.fetched_at)));
+        Ok(entries)
+    }
+
+    /// remove every file named like a cache entry, readable or not, then the dir if that left it empty
+    ///
+    /// the dir can be any path given with --cache-dir, so nothing else in it is touched
+    fn clear(&self) -> Result<usize> {
+        let dir = match fs::read_dir(&self.dir) {
+            Ok(dir) => dir,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(0),
+            Err(err) => {
+                return Err(Error::io(format!("Could not read cache dir {}", self.dir.display()), err))
+            }
+        };
+
+        let mut removed = 0;
+        for file in dir.flatten() {
+            let is_file = file.file_type().is_ok_and(|kind| kind.is_file());
+            if let Some(key) = file.file_name().to_str().and_then(CacheKey::from_file_name).filter(|_| is_file) {
+                self.remove(&key)?;
+                removed += 1;
+            }
+        }
+        // fails if anything else is left in the dir, which is then kept
+        let _ = fs::remove_dir(&self.dir);
+        Ok(removed)
+    }
+}
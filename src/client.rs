@@ -0,0 +1,668 @@
+use chrono::{DateTime, Utc};
+
+use reqwest::blocking::{RequestBuilder, Response};
+use reqwest::header::{self, HeaderMap};
+use reqwest::{StatusCode, Url};
+
+use crate::auth::{resolve_token, AuthOptions};
+use crate::cache::{Cache, CacheEntry, CacheKey, CachedPage, CACHE_VERSION};
+use crate::error::{Error, Result};
+use crate::history::History;
+use crate::rate_limit::{RateLimit, RateLimits};
+use crate::repo::{parse_starred, Repo};
+use crate::source::Source;
+
+use std::env;
+use std::thread;
+use std::time::Duration;
+
+const API_URL: &str = "https://api.github.com";
+const PER_PAGE: usize = 100;
+/// starred list media type that includes when each repo was starred
+const STAR_MEDIA_TYPE: &str = "application/vnd.github.star+json";
+const MEDIA_TYPE: &str = "application/vnd.github+json";
+/// github serves at most this many results of a search
+pub const SEARCH_RESULT_LIMIT: usize = 1000;
+/// how long a cached list is used before asking github whether it changed
+pub const DEFAULT_MAX_AGE: Duration = Duration::from_secs(60 * 60);
+/// how often a request failing with a 5xx or network error is retried
+pub const DEFAULT_RETRIES: u32 = 3;
+/// delay before the first retry, doubled for each further one
+pub const DEFAULT_BACKOFF: Duration = Duration::from_secs(1);
+/// default shortest wait for the rate limit, a reset already past on the local clock would mean none
+pub const MIN_RATE_LIMIT_WAIT: Duration = Duration::from_secs(1);
+/// how often one request waits for the rate limit before giving up
+const MAX_RATE_LIMIT_WAITS: u32 = 3;
+/// longest a server error's `Retry-After` delays a retry
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+type OnHistoryError = Box<dyn Fn(&Error)>;
+
+/// client for the starred endpoint of the github api
+pub struct StarredClient {
+    http: reqwest::blocking::Client,
+    api_url: String,
+    token: Option<String>,
+    cache: Option<Box<dyn Cache>>,
+    history: Option<History>,
+    max_age: Duration,
+    cache_mode: CacheMode,
+    retries: u32,
+    backoff: Duration,
+    wait_for_rate_limit: bool,
+    min_rate_limit_wait: Duration,
+    /// told how long the client sleeps before each rate limit wait
+    on_rate_limit_wait: Option<Box<dyn Fn(Duration)>>,
+    /// told why a list could not be recorded, instead of failing the fetch
+    on_history_error: Option<OnHistoryError>,
+}
+
+/// how the client uses the cache
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CacheMode {
+    /// use fresh lists as is, revalidate stale ones
+    #[default]
+    Normal,
+    /// serve cached lists whatever their age and never touch the network
+    Offline,
+    /// fetch the whole list even if a fresh one is cached, still updating the cache
+    Refresh,
+}
+
+/// a starred list and when it was fetched
+#[derive(Debug)]
+pub struct Fetched {
+    pub repos: Vec<Repo>,
+    /// when github last sent or confirmed this list
+    pub fetched_at: DateTime<Utc>,
+    /// whether github was asked, either for the list or whether it changed
+    pub from_network: bool,
+}
+
+/// what listing a source got
+enum Listing {
+    /// every cached page is unchanged
+    NotModified,
+    /// stopped at the limit
+    Partial(Vec<Repo>),
+    Complete {
+        repos: Vec<Repo>,
+        pages: Vec<CachedPage>,
+    },
+}
+
+/// one page of a list
+enum Page {
+    NotModified {
+        next: Option<String>,
+    },
+    Items {
+        items: Vec<serde_json::Value>,
+        next: Option<String>,
+        etag: Option<String>,
+        last_modified: Option<String>,
+    },
+}
+
+impl Default for StarredClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StarredClient {
+    /// client for api.github.com without token or cache
+    pub fn new() -> Self {
+        StarredClient {
+            http: reqwest::blocking::Client::new(),
+            api_url: API_URL.to_string(),
+            token: None,
+            cache: None,
+            history: None,
+            max_age: DEFAULT_MAX_AGE,
+            cache_mode: CacheMode::default(),
+            retries: DEFAULT_RETRIES,
+            backoff: DEFAULT_BACKOFF,
+            wait_for_rate_limit: false,
+            min_rate_limit_wait: MIN_RATE_LIMIT_WAIT,
+            on_rate_limit_wait: None,
+            on_history_error: None,
+        }
+    }
+
+    /// client using `GITHUB_API_URL` if set and a token from the default sources,
+    /// see `auth::resolve_token`
+    pub fn from_env() -> Result<Self> {
+        Self::from_env_with(&AuthOptions::default())
+    }
+
+    /// like `from_env`, looking for the token as configured by `auth`
+    pub fn from_env_with(auth: &AuthOptions) -> Result<Self> {
+        let mut client = Self::new();
+        if let Ok(api_url) = env::var("GITHUB_API_URL") {
+            client = client.with_api_url(api_url);
+        }
+        if let Some(token) = resolve_token(auth)? {
+            client = client.with_token(token.value);
+        }
+        Ok(client)
+    }
+
+    /// use a different api root, e.g. for github enterprise
+    pub fn with_api_url(mut self, api_url: impl Into<String>) -> Self {
+        self.api_url = api_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// authenticate requests, without a token they are anonymous
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    pub fn with_cache(mut self, cache: impl Cache + 'static) -> Self {
+        self.cache = Some(Box::new(cache));
+        self
+    }
+
+    /// record every complete list fetched from github in history
+    pub fn with_history(mut self, history: History) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    /// how long cached lists are used as is, older ones are revalidated
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    pub fn with_cache_mode(mut self, cache_mode: CacheMode) -> Self {
+        self.cache_mode = cache_mode;
+        self
+    }
+
+    /// retry failed requests this often, waiting `backoff`, then twice as long and so on
+    pub fn with_retries(mut self, retries: u32, backoff: Duration) -> Self {
+        self.retries = retries;
+        self.backoff = backoff;
+        self
+    }
+
+    /// sleep until the quota is refilled instead of failing when rate limited
+    pub fn with_wait_for_rate_limit(mut self, wait: bool) -> Self {
+        self.wait_for_rate_limit = wait;
+        self
+    }
+
+    /// wait at least this long for the rate limit, whatever github says
+    pub fn with_min_rate_limit_wait(mut self, wait: Duration) -> Self {
+        self.min_rate_limit_wait = wait;
+        self
+    }
+
+    /// call notify with how long the client is about to sleep for the rate limit,
+    /// e.g. to tell the user why nothing happens
+    pub fn on_rate_limit_wait(mut self, notify: impl Fn(Duration) + 'static) -> Self {
+        self.on_rate_limit_wait = Some(Box::new(notify));
+        self
+    }
+
+    /// call notify when a fetched list can't be recorded in the history and go on,
+    /// without it such a fetch fails, though the list is still cached
+    pub fn on_history_error(mut self, notify: impl Fn(&Error) + 'static) -> Self {
+        self.on_history_error = Some(Box::new(notify));
+        self
+    }
+
+    /// current quotas, asking for them doesn't count against any
+    pub fn rate_limit(&self) -> Result<RateLimits> {
+        let url = format!("{}/rate_limit", self.api_url);
+        let res = check_status(&url, self.send(self.get(&url))?)?;
+        parse_json(&url, &res.text()?)
+    }
+
+    /// starred repos of user, see `fetch`
+    pub fn starred(&self, user: &str, limit: Option<usize>) -> Result<Vec<Repo>> {
+        Ok(self.fetch_starred(user, limit)?.repos)
+    }
+
+    /// starred list of user, see `fetch`
+    pub fn fetch_starred(&self, user: &str, limit: Option<usize>) -> Result<Fetched> {
+        self.fetch(&Source::Starred(user.to_string()), limit)
+    }
+
+    /// make requests to github api for source, following pagination until exhausted or `limit` is reached
+    ///
+    /// a cached list younger than the max age is used as is. each page of an older one
+    /// is revalidated with its own `ETag`/`Last-Modified`, a 304 only vouches for the page it answers:
+    /// unstarring repo 150 or a star count changing on page 2 leaves the first page as it was.
+    /// the list is reused if every page answers 304, else changed pages are fetched again,
+    /// 304s don't count against the rate limit.
+    /// the cache mode can restrict this to the cache or skip the cache.
+    /// complete lists from github are also recorded in the history, if there is one
+    pub fn fetch(&self, source: &Source, limit: Option<usize>) -> Result<Fetched> {
+        let key = self.source_cache_key(source)?;
+        let cached = match self.cache_mode {
+            CacheMode::Refresh => None,
+            _ => self.cache.as_ref().and_then(|cache| cache.get(&key)),
+        };
+        let usable = match self.cache_mode {
+            CacheMode::Offline => true,
+            _ => cached.as_ref().is_some_and(|entry| entry.is_fresh(self.max_age)),
+        };
+        if usable {
+            let entry = cached.ok_or_else(|| Error::NotCached(source.to_string()))?;
+            return Ok(Fetched {
+                repos: truncate(entry.repos, limit),
+                fetched_at: entry.fetched_at,
+                from_network: false,
+            });
+        }
+
+        let listing = match source {
+            Source::Repos(names) => self.get_repos(names, limit)?,
+            _ => self.get_list(source, cached.as_ref(), limit)?,
+        };
+        let fetched_at = Utc::now();
+
+        let (repos, pages) = match listing {
+            Listing::NotModified => {
+                // github only answers 304 to the conditional request made with a cached list
+                let mut entry = cached.ok_or_else(|| Error::Status {
+                    url: self.first_page_url(source).unwrap_or_default(),
+                    status: StatusCode::NOT_MODIFIED.as_u16(),
+                })?;
+                entry.fetched_at = fetched_at;
+                self.write_cache(&key, &entry)?;
+                // nothing was fetched, the history only gets lists github sent
+                return Ok(Fetched {
+                    repos: truncate(entry.repos, limit),
+                    fetched_at: entry.fetched_at,
+                    from_network: true,
+                });
+            }
+            // only cache and record complete lists, a later run without --limit must not see a truncated one
+            Listing::Partial(repos) => {
+                return Ok(Fetched {
+                    repos: truncate(repos, limit),
+                    fetched_at,
+                    from_network: true,
+                })
+            }
+            Listing::Complete { repos, pages } => (repos, pages),
+        };
+
+        self.write_cache(
+            &key,
+            &CacheEntry {
+                version: CACHE_VERSION,
+                user: source.label(),
+                fetched_at,
+                pages,
+                repos: repos.clone(),
+            },
+        )?;
+        // after caching, so a history failure never costs the fetch
+        if let Err(err) = self.record_history(source, fetched_at, &repos) {
+            match &self.on_history_error {
+                Some(notify) => notify(&err),
+                None => return Err(err),
+            }
+        }
+
+        Ok(Fetched {
+            repos: truncate(repos, limit),
+            fetched_at,
+            from_network: true,
+        })
+    }
+
+    /// follow the pages of a listing endpoint, asking whether each page of cached changed
+    /// and reusing the repos of those that didn't
+    fn get_list(&self, source: &Source, cached: Option<&CacheEntry>, limit: Option<usize>) -> Result<Listing> {
+        let cached_pages = cached.map(split_pages).unwrap_or_default();
+        let mut next_url = Some(self.first_page_url(source)?);
+
+        let mut repos: Vec<Repo> = Vec::new();
+        let mut pages: Vec<CachedPage> = Vec::new();
+        let mut modified = false;
+        while let Some(url) = next_url.take() {
+            let index = pages.len();
+            // a page is only comparable to the cached one fetched from the same url
+            let cached_page = cached_pages.get(index).filter(|(page, _)| page.url == url);
+            match self.get_page(source, &url, cached_page.map(|&(page, _)| page))? {
+                Page::NotModified { next } => {
+                    // github only answers 304 to a conditional request, made with a cached page
+                    let &(page, page_repos) = cached_page.ok_or_else(|| Error::Status {
+                        url: url.clone(),
+                        status: StatusCode::NOT_MODIFIED.as_u16(),
+                    })?;
+                    // without a link on the 304 the page after it is the cached one
+                    next_url = next.or_else(|| cached_pages.get(index + 1).map(|(page, _)| page.url.clone()));
+                    repos.extend_from_slice(page_repos);
+                    pages.push(page.clone());
+                }
+                Page::Items {
+                    items,
+                    next,
+                    etag,
+                    last_modified,
+                } => {
+                    let page_repos = parse_starred(items)
+                        .map_err(|err| Error::parse(format!("Could not parse {}", source), err))?;
+                    pages.push(CachedPage {
+                        url,
+                        etag,
+                        last_modified,
+                        count: page_repos.len(),
+                    });
+                    repos.extend(page_repos);
+                    next_url = next;
+                    modified = true;
+                }
+            }
+            // asking for more than the first results of a search fails with 422
+            if matches!(source, Source::Search(_)) && repos.len() >= SEARCH_RESULT_LIMIT {
+                next_url = None;
+            }
+
+            if limit.is_some_and(|limit| repos.len() >= limit) {
+                break;
+            }
+        }
+
+        Ok(match next_url {
+            Some(_) => Listing::Partial(repos),
+            // the last pages can be gone while the ones before them are unchanged
+            None if !modified && pages.len() == cached_pages.len() => Listing::NotModified,
+            None => Listing::Complete { repos, pages },
+        })
+    }
+
+    /// get each repo by full name, there is no endpoint listing several at once
+    fn get_repos(&self, names: &[String], limit: Option<usize>) -> Result<Listing> {
+        let mut repos = Vec::new();
+        for name in names.iter().take(limit.unwrap_or(usize::MAX)) {
+            let url = format!("{}/repos/{}", self.api_url, name);
+            let res = self.send(self.get(&url).header(header::ACCEPT, MEDIA_TYPE))?;
+            if res.status() == StatusCode::NOT_FOUND {
+                return Err(Error::RepoNotFound(name.clone()));
+            }
+            let res = check_status(&url, res)?;
+            repos.push(parse_json(&url, &res.text()?)?);
+        }
+
+        Ok(if repos.len() < names.len() {
+            Listing::Partial(repos)
+        } else {
+            Listing::Complete {
+                repos,
+                pages: Vec::new(),
+            }
+        })
+    }
+
+    /// url of the first page listing source
+    fn first_page_url(&self, source: &Source) -> Result<String> {
+        Ok(match source {
+            Source::Starred(user) => format!("{}/users/{}/starred?per_page={}", self.api_url, user, PER_PAGE),
+            Source::Org(org) => format!("{}/orgs/{}/repos?per_page={}", self.api_url, org, PER_PAGE),
+            Source::OwnedBy(user) => {
+                format!("{}/users/{}/repos?type=owner&per_page={}", self.api_url, user, PER_PAGE)
+            }
+            Source::Search(query) => Url::parse_with_params(
+                &format!("{}/search/repositories", self.api_url),
+                &[
+                    ("q", query.as_str()),
+                    ("sort", "stars"),
+                    ("order", "desc"),
+                    ("per_page", &PER_PAGE.to_string()),
+                ],
+            )
+            .map_err(|err| Error::invalid(format!("Invalid api url {}: {}", self.api_url, err)))?
+            .to_string(),
+            // fetched one by one, see `get_repos`
+            Source::Repos(_) => format!("{}/repos", self.api_url),
+        })
+    }
+
+    /// get one page, conditional on `cached` being unchanged if given
+    fn get_page(&self, source: &Source, url: &str, cached: Option<&CachedPage>) -> Result<Page> {
+        let mut req = self.get(url).header(header::ACCEPT, media_type(source));
+        if let Some(page) = cached {
+            if let Some(etag) = &page.etag {
+                req = req.header(header::IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &page.last_modified {
+                req = req.header(header::IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let res = self.send(req)?;
+
+        match (res.status(), source) {
+            (StatusCode::NOT_MODIFIED, _) => {
+                return Ok(Page::NotModified {
+                    next: next_link(res.headers()),
+                })
+            }
+            (StatusCode::NOT_FOUND, Source::Starred(user) | Source::OwnedBy(user)) => {
+                return Err(Error::UserNotFound(user.clone()))
+            }
+            (StatusCode::NOT_FOUND, Source::Org(org)) => return Err(Error::OrgNotFound(org.clone())),
+            (StatusCode::UNPROCESSABLE_ENTITY, Source::Search(query)) => {
+                return Err(Error::invalid(format!("GitHub rejected the search query {:?}", query)))
+            }
+            _ => (),
+        }
+        let res = check_status(url, res)?;
+
+        let headers = res.headers();
+        let next = next_link(headers);
+        let etag = header_value(headers, header::ETAG);
+        let last_modified = header_value(headers, header::LAST_MODIFIED);
+        let body: serde_json::Value = parse_json(url, &res.text()?)?;
+        // search results wrap the repos in an object
+        let items = match body {
+            serde_json::Value::Object(mut object) => object.remove("items").unwrap_or_default(),
+            body => body,
+        };
+        let items = serde_json::from_value(items)
+            .map_err(|err| Error::parse(format!("Could not parse response of {}", url), err))?;
+        Ok(Page::Items {
+            items,
+            next,
+            etag,
+            last_modified,
+        })
+    }
+
+    /// GET request to the api, authenticated if there is a token
+    fn get(&self, url: &str) -> RequestBuilder {
+        let req = self.http.get(url).header("User-Agent", "starred-repos");
+        match &self.token {
+            Some(token) => req.header(header::AUTHORIZATION, format!("Bearer {}", token)),
+            None => req,
+        }
+    }
+
+    /// send req, retrying 5xx and network errors with exponential backoff
+    /// and waiting out the rate limit if allowed to, up to `MAX_RATE_LIMIT_WAITS` times
+    fn send(&self, req: RequestBuilder) -> Result<Response> {
+        let mut attempt = 0;
+        let mut waits = 0;
+        loop {
+            // only requests with streamed bodies can't be cloned, these are all GETs
+            let res = req.try_clone().expect("GET requests can be cloned").send();
+            let backoff = self.backoff.saturating_mul(1 << attempt.min(16));
+
+            let delay = match res {
+                Ok(res) => {
+                    if let Some(wait) = rate_limit_wait(&res) {
+                        let wait = wait.max(self.min_rate_limit_wait);
+                        if !self.wait_for_rate_limit || waits >= MAX_RATE_LIMIT_WAITS {
+                            return Err(Error::RateLimited {
+                                reset_in: wait,
+                                authenticated: self.token.is_some(),
+                            });
+                        }
+                        waits += 1;
+                        if let Some(notify) = &self.on_rate_limit_wait {
+                            notify(wait);
+                        }
+                        thread::sleep(wait);
+                        continue;
+                    }
+                    if !res.status().is_server_error() || attempt >= self.retries {
+                        return Ok(res);
+                    }
+                    retry_after(res.headers()).map_or(backoff, |wait| wait.min(MAX_RETRY_AFTER))
+                }
+                Err(err) if (err.is_connect() || err.is_timeout()) && attempt < self.retries => backoff,
+                Err(err) => return Err(Error::Network(err)),
+            };
+
+            attempt += 1;
+            thread::sleep(delay);
+        }
+    }
+
+    /// key the starred list of user is cached under, fails for invalid logins
+    pub fn cache_key(&self, user: &str) -> Result<CacheKey> {
+        self.source_cache_key(&Source::Starred(user.to_string()))
+    }
+
+    /// key the list of source is cached under, fails for invalid logins or names
+    pub fn source_cache_key(&self, source: &Source) -> Result<CacheKey> {
+        source.validate()?;
+        let per_page = PER_PAGE.to_string();
+        let params = [
+            ("api", self.api_url.as_str()),
+            ("per_page", per_page.as_str()),
+            ("accept", media_type(source)),
+        ];
+        match source {
+            Source::Starred(user) => CacheKey::starred(user, &params),
+            Source::Org(org) => {
+                let org = org.to_lowercase();
+                CacheKey::new(&format!("org-{}", org), &format!("orgs/{}/repos", org), &params)
+            }
+            Source::OwnedBy(user) => {
+                let user = user.to_lowercase();
+                CacheKey::new(&format!("owned-{}", user), &format!("users/{}/repos?type=owner", user), &params)
+            }
+            Source::Search(query) => {
+                CacheKey::new("search", &format!("search/repositories?q={}&sort=stars", query), &params)
+            }
+            Source::Repos(names) => {
+                let names: Vec<_> = names.iter().map(|name| name.to_lowercase()).collect();
+                CacheKey::new("list", &format!("repos/{}", names.join(",")), &params)
+            }
+        }
+    }
+
+    /// lists from a file or a search are one-offs nobody follows over time, they aren't recorded
+    fn record_history(&self, source: &Source, fetched_at: DateTime<Utc>, repos: &[Repo]) -> Result<()> {
+        match (&self.history, source) {
+            (_, Source::Repos(_) | Source::Search(_)) | (None, _) => Ok(()),
+            (Some(history), _) => history.record(&source.label(), fetched_at, repos),
+        }
+    }
+
+    fn write_cache(&self, key: &CacheKey, entry: &CacheEntry) -> Result<()> {
+        match &self.cache {
+            Some(cache) => cache.put(key, entry),
+            None => Ok(()),
+        }
+    }
+}
+
+/// the repos each cached page holds, none if the counts don't add up to the cached repos
+fn split_pages(entry: &CacheEntry) -> Vec<(&CachedPage, &[Repo])> {
+    let mut rest = entry.repos.as_slice();
+    let mut pages = Vec::new();
+    for page in &entry.pages {
+        if page.count > rest.len() {
+            return Vec::new();
+        }
+        let (page_repos, after) = rest.split_at(page.count);
+        pages.push((page, page_repos));
+        rest = after;
+    }
+    if !rest.is_empty() {
+        return Vec::new();
+    }
+    pages
+}
+
+fn truncate(mut repos: Vec<Repo>, limit: Option<usize>) -> Vec<Repo> {
+    if let Some(limit) = limit {
+        repos.truncate(limit);
+    }
+    repos
+}
+
+fn media_type(source: &Source) -> &'static str {
+    match source {
+        Source::Starred(_) => STAR_MEDIA_TYPE,
+        _ => MEDIA_TYPE,
+    }
+}
+
+/// pass successful responses through, turning the others into errors
+fn check_status(url: &str, res: Response) -> Result<Response> {
+    let status = res.status();
+    if status.is_success() {
+        return Ok(res);
+    }
+    Err(match status {
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Error::Auth {
+            status: status.as_u16(),
+        },
+        _ => Error::Status {
+            url: url.to_string(),
+            status: status.as_u16(),
+        },
+    })
+}
+
+fn parse_json<T: serde::de::DeserializeOwned>(url: &str, body: &str) -> Result<T> {
+    serde_json::from_str(body).map_err(|err| Error::parse(format!("Could not parse response of {}", url), err))
+}
+
+/// how long to wait if res says the rate limit is exceeded
+/// github answers 403 or 429 with either `Retry-After` or an exhausted `X-RateLimit-Remaining`
+fn rate_limit_wait(res: &Response) -> Option<Duration> {
+    if !matches!(res.status(), StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS) {
+        return None;
+    }
+    retry_after(res.headers()).or_else(|| {
+        RateLimit::from_headers(res.headers())
+            .filter(|limit| limit.remaining == 0)
+            .map(|limit| limit.reset_in())
+    })
+}
+
+/// `Retry-After` in seconds, github never sends the date form
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let seconds = header_value(headers, header::RETRY_AFTER)?.trim().parse().ok()?;
+    Some(Duration::from_secs(seconds))
+}
+
+fn header_value(headers: &HeaderMap, name: header::HeaderName) -> Option<String> {
+    Some(headers.get(name)?.to_str().ok()?.to_string())
+}
+
+/// get the url marked rel="next" from the Link header
+/// returns None on the last page
+fn next_link(headers: &HeaderMap) -> Option<String> {
+    let link = headers.get(header::LINK)?.to_str().ok()?;
+    link.split(',').find_map(|part| {
+        let mut params = part.split(';');
+        let url = params.next()?.trim().strip_prefix('<')?.strip_suffix('>')?;
+        params
+            .any(|param| param.trim() == "rel=\"next\"")
+            .then(|| url.to_string())
+    })
+}
@@ -0,0 +1,117 @@
+use serde::Serialize;
+
+use crate::error::{Error, Result};
+use crate::repo::Repo;
+
+use std::collections::HashSet;
+
+/// how the starred lists of several users overlap
+#[derive(Debug, Clone, Serialize)]
+pub struct Comparison {
+    pub users: Vec<String>,
+    /// repos every user starred, in the order of the first user's list
+    pub common: Vec<Repo>,
+    /// repos starred by only one of the users
+    pub unique: Vec<UserRepos>,
+    /// number of distinct repos starred by any of the users
+    pub total: usize,
+    /// jaccard index over all lists, shared repos divided by all repos
+    pub similarity: f64,
+    /// jaccard index of every pair of users
+    pub pairs: Vec<Pair>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UserRepos {
+    pub user: String,
+    pub repos: Vec<Repo>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Pair {
+    pub users: (String, String),
+    /// number of repos both starred
+    pub common: usize,
+    pub similarity: f64,
+}
+
+impl Comparison {
+    /// compare the starred list of each user, repos are matched by url
+    pub fn new(lists: Vec<(String, Vec<Repo>)>) -> Self {
+        let sets: Vec<HashSet<String>> = lists.iter().map(|(_, repos)| urls(repos)).collect();
+
+        let union: HashSet<&String> = sets.iter().flatten().collect();
+        let in_all = |url: &String| sets.iter().all(|set| set.contains(url));
+        let in_others = |i: usize, url: &String| {
+            sets.iter()
+                .enumerate()
+                .any(|(j, set)| i != j && set.contains(url))
+        };
+
+        let common: Vec<Repo> = lists
+            .first()
+            .map(|(_, repos)| {
+                dedup(repos)
+                    .filter(|repo| in_all(&repo.identity()))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default();
+        let unique = lists
+            .iter()
+            .enumerate()
+            .map(|(i, (user, repos))| UserRepos {
+                user: user.clone(),
+                repos: dedup(repos)
+                    .filter(|repo| !in_others(i, &repo.identity()))
+                    .cloned()
+                    .collect(),
+            })
+            .collect();
+
+        let mut pairs = Vec::new();
+        for i in 0..lists.len() {
+            for j in i + 1..lists.len() {
+                let common = sets[i].intersection(&sets[j]).count();
+                let union = sets[i].union(&sets[j]).count();
+                pairs.push(Pair {
+                    users: (lists[i].0.clone(), lists[j].0.clone()),
+                    common,
+                    similarity: jaccard(common, union),
+                });
+            }
+        }
+
+        Comparison {
+            users: lists.into_iter().map(|(user, _)| user).collect(),
+            similarity: jaccard(common.len(), union.len()),
+            total: union.len(),
+            common,
+            unique,
+            pairs,
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(self).map_err(|err| Error::parse("Could not serialize comparison as json", err))
+    }
+}
+
+fn urls(repos: &[Repo]) -> HashSet<String> {
+    repos.iter().map(Repo::identity).collect()
+}
+
+/// repos in order, skipping ones listed before
+fn dedup(repos: &[Repo]) -> impl Iterator<Item = &Repo> {
+    let mut seen = HashSet::new();
+    repos.iter().filter(move |repo| seen.insert(repo.identity()))
+}
+
+/// 0 for two empty lists, which have nothing to be similar in
+fn jaccard(common: usize, union: usize) -> f64 {
+    if union == 0 {
+        0.0
+    } else {
+        common as f64 / union as f64
+    }
+}
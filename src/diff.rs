@@ -0,0 +1,121 @@
+use serde::Serialize;
+
+use crate::error::{Error, Result};
+use crate::format;
+use crate::repo::Repo;
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// what changed in a starred list since a snapshot
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Diff {
+    /// starred since, in the order of the current list
+    pub added: Vec<Repo>,
+    /// unstarred since, in the order of the snapshot
+    pub removed: Vec<Repo>,
+    /// in both, with a different star count or description
+    pub changed: Vec<Change>,
+}
+
+/// a list saved by the json or toml export
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    /// whose list it is, only toml exports of starred lists record that
+    pub user: Option<String>,
+    /// where a toml export that isn't a starred list came from, e.g. `org:rust-lang`
+    pub source: Option<String>,
+    pub repos: Vec<Repo>,
+}
+
+impl Snapshot {
+    /// read a snapshot, as toml if path ends in `.toml` and as json otherwise
+    pub fn load(path: &Path) -> Result<Self> {
+        let input = fs::read_to_string(path)
+            .map_err(|err| Error::io(format!("Could not read snapshot {}", path.display()), err))?;
+        if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("toml")) {
+            let export = format::from_toml(&input)?;
+            Ok(Snapshot {
+                user: export.meta.user,
+                source: export.meta.source,
+                repos: export.repos,
+            })
+        } else {
+            Ok(Snapshot {
+                user: None,
+                source: None,
+                repos: format::from_json(&input)?,
+            })
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Change {
+    pub full_name: String,
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stars: Option<Changed<u64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<Changed<Option<String>>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Changed<T> {
+    pub before: T,
+    pub after: T,
+}
+
+impl Diff {
+    /// compare the list in snapshot `before` with the current one, repos are matched by url
+    pub fn new(before: &[Repo], after: &[Repo]) -> Self {
+        let before_by_id: HashMap<String, &Repo> =
+            before.iter().map(|repo| (repo.identity(), repo)).collect();
+        let after_by_id: HashMap<String, &Repo> =
+            after.iter().map(|repo| (repo.identity(), repo)).collect();
+
+        let mut diff = Diff::default();
+        for repo in after {
+            match before_by_id.get(&repo.identity()) {
+                Some(old) => diff.changed.extend(Change::between(old, repo)),
+                None => diff.added.push(repo.clone()),
+            }
+        }
+        diff.removed = before
+            .iter()
+            .filter(|repo| !after_by_id.contains_key(&repo.identity()))
+            .cloned()
+            .collect();
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(self).map_err(|err| Error::parse("Could not serialize diff as json", err))
+    }
+}
+
+impl Change {
+    /// None if nothing tracked changed
+    fn between(before: &Repo, after: &Repo) -> Option<Self> {
+        let stars = (before.star_count != after.star_count).then_some(Changed {
+            before: before.star_count,
+            after: after.star_count,
+        });
+        let description = (before.description() != after.description()).then(|| Changed {
+            before: before.description().map(String::from),
+            after: after.description().map(String::from),
+        });
+
+        (stars.is_some() || description.is_some()).then(|| Change {
+            full_name: after.full_name.clone(),
+            url: after.url.clone(),
+            stars,
+            description,
+        })
+    }
+}
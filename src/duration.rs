@@ -0,0 +1,61 @@
+use crate::error::{Error, Result};
+
+use std::time::Duration;
+
+/// parse a duration like `90s`, `15m`, `1h`, `7d` or `2w`
+pub fn parse_duration(input: &str) -> Result<Duration> {
+    let input = input.trim();
+    let split = input
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(input.len());
+    let (amount, unit) = input.split_at(split);
+    let amount: u64 = amount
+        .parse()
+        .map_err(|_| Error::invalid(format!("invalid duration {:?}, expected e.g. 1h or 7d", input)))?;
+
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => {
+            return Err(Error::invalid(format!(
+                "invalid duration unit in {:?}, expected one of s, m, h, d, w",
+                input
+            )))
+        }
+    };
+    let seconds = amount
+        .checked_mul(seconds)
+        .ok_or_else(|| Error::invalid(format!("duration {:?} is too long", input)))?;
+    Ok(Duration::from_secs(seconds))
+}
+
+/// format a duration with its two largest units, e.g. `3d 2h` or `5m`
+pub fn format_duration(duration: Duration) -> String {
+    const UNITS: &[(&str, u64)] = &[
+        ("w", 7 * 24 * 60 * 60),
+        ("d", 24 * 60 * 60),
+        ("h", 60 * 60),
+        ("m", 60),
+        ("s", 1),
+    ];
+
+    let mut left = duration.as_secs();
+    let parts: Vec<String> = UNITS
+        .iter()
+        .filter_map(|(unit, seconds)| {
+            let amount = left / seconds;
+            left %= seconds;
+            (amount > 0).then(|| format!("{}{}", amount, unit))
+        })
+        .take(2)
+        .collect();
+
+    if parts.is_empty() {
+        "0s".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
@@ -0,0 +1,125 @@
+use thiserror::Error;
+
+use crate::duration::format_duration;
+
+use std::io;
+use std::time::Duration;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// what went wrong, each kind maps to its own exit code of the cli
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("GitHub user {0} does not exist")]
+    UserNotFound(String),
+
+    #[error("GitHub organization {0} does not exist")]
+    OrgNotFound(String),
+
+    #[error("GitHub repository {0} does not exist")]
+    RepoNotFound(String),
+
+    /// offline without a cached list, holds what the list is of, see `Source`'s `Display`
+    #[error("No cached {0}, run without --offline to fetch it")]
+    NotCached(String),
+
+    /// github refused the token, or anonymous access where it is required
+    #[error("GitHub refused the request with status {status}, check that the token is valid and not expired")]
+    Auth { status: u16 },
+
+    #[error(
+        "GitHub rate limit exceeded, it resets in {}. Rerun with --wait to wait for it{}",
+        format_duration(*reset_in),
+        if *authenticated { "" } else { " or use a token for a higher limit" }
+    )]
+    RateLimited { reset_in: Duration, authenticated: bool },
+
+    /// github answered with an unexpected status, even after retrying
+    #[error("{url} returned error with status {status}")]
+    Status { url: String, status: u16 },
+
+    #[error("Could not connect to github api")]
+    Network(#[from] reqwest::Error),
+
+    /// reading or writing json, toml or csv failed
+    #[error("{context}")]
+    Parse {
+        context: String,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    #[error("{context}")]
+    Io {
+        context: String,
+        #[source]
+        source: io::Error,
+    },
+
+    /// reading or writing the history database failed
+    #[error("{context}")]
+    History {
+        context: String,
+        #[source]
+        source: rusqlite::Error,
+    },
+
+    /// bad user input, e.g. an invalid login, duration or sort key
+    #[error("{0}")]
+    Invalid(String),
+}
+
+impl Error {
+    pub(crate) fn parse(
+        context: impl Into<String>,
+        source: impl std::error::Error + Send + Sync + 'static,
+    ) -> Self {
+        Error::Parse {
+            context: context.into(),
+            source: Box::new(source),
+        }
+    }
+
+    pub(crate) fn io(context: impl Into<String>, source: io::Error) -> Self {
+        Error::Io {
+            context: context.into(),
+            source,
+        }
+    }
+
+    pub(crate) fn history(context: impl Into<String>, source: rusqlite::Error) -> Self {
+        Error::History {
+            context: context.into(),
+            source,
+        }
+    }
+
+    pub(crate) fn invalid(message: impl Into<String>) -> Self {
+        Error::Invalid(message.into())
+    }
+
+    /// process exit code for this error, 1 is left to clap's usage errors
+    ///
+    /// | code | error                                    |
+    /// |------|------------------------------------------|
+    /// | 2    | invalid input                            |
+    /// | 3    | user, org, repo or cached list not found |
+    /// | 4    | authentication                           |
+    /// | 5    | rate limit                               |
+    /// | 6    | network or server error                  |
+    /// | 7    | parsing or serializing                   |
+    /// | 8    | file system                              |
+    /// | 9    | history database                         |
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Invalid(_) => 2,
+            Error::UserNotFound(_) | Error::OrgNotFound(_) | Error::RepoNotFound(_) | Error::NotCached(_) => 3,
+            Error::Auth { .. } => 4,
+            Error::RateLimited { .. } => 5,
+            Error::Network(_) | Error::Status { .. } => 6,
+            Error::Parse { .. } => 7,
+            Error::Io { .. } => 8,
+            Error::History { .. } => 9,
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use chrono::{DateTime, Utc};
+
+use crate::repo::Repo;
+
+/// criteria a repo has to meet to be listed
+///
+/// every set criterion has to match, the default filter keeps everything
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    /// primary language, case insensitive
+    pub language: Option<String>,
+    /// one of the repo topics, case insensitive
+    pub topic: Option<String>,
+    /// owner login, case insensitive
+    pub owner: Option<String>,
+    pub min_stars: Option<u64>,
+    /// starred at or after this time, repos without star date never match
+    pub starred_after: Option<DateTime<Utc>>,
+    pub exclude_archived: bool,
+    pub exclude_forks: bool,
+}
+
+impl Filter {
+    pub fn matches(&self, repo: &Repo) -> bool {
+        let language = self.language.as_ref().is_none_or(|language| {
+            repo.language
+                .as_ref()
+                .is_some_and(|l| l.eq_ignore_ascii_case(language))
+        });
+        let topic = self.topic.as_ref().is_none_or(|topic| {
+            repo.topics.iter().any(|t| t.eq_ignore_ascii_case(topic))
+        });
+        let owner = self
+            .owner
+            .as_ref()
+            .is_none_or(|owner| repo.owner.login.eq_ignore_ascii_case(owner));
+        let stars = self.min_stars.is_none_or(|min| repo.star_count >= min);
+        let starred = self
+            .starred_after
+            .is_none_or(|after| repo.starred_at.is_some_and(|at| at >= after));
+
+        language
+            && topic
+            && owner
+            && stars
+            && starred
+            && !(self.exclude_archived && repo.archived)
+            && !(self.exclude_forks && repo.fork)
+    }
+
+    /// keep only the repos matching this filter
+    pub fn apply(&self, repos: Vec<Repo>) -> Vec<Repo> {
+        repos.into_iter().filter(|repo| self.matches(repo)).collect()
+    }
+}
@@ -0,0 +1,114 @@
+use crate::error::{Error, Result};
+
+use crate::repo::Repo;
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::str::FromStr;
+
+/// heading used for repos without language or topics
+const OTHER: &str = "Other";
+
+/// how repos are split into sections
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GroupBy {
+    #[default]
+    Language,
+    /// repos show up under every one of their topics
+    Topic,
+}
+
+impl FromStr for GroupBy {
+    type Err = Error;
+
+    fn from_str(group_by: &str) -> Result<Self> {
+        Ok(match group_by.trim() {
+            "language" => GroupBy::Language,
+            "topic" => GroupBy::Topic,
+            other => {
+                return Err(Error::invalid(format!(
+                    "unknown grouping {:?}, expected language or topic",
+                    other
+                )))
+            }
+        })
+    }
+}
+
+/// render repos as an awesome list: a table of contents and one section per group,
+/// repos keep their order within a section
+pub fn to_markdown(title: &str, repos: &[Repo], group_by: GroupBy) -> String {
+    let mut groups: BTreeMap<&str, Vec<&Repo>> = BTreeMap::new();
+    let mut other = Vec::new();
+    for repo in repos {
+        let keys: Vec<&str> = match group_by {
+            GroupBy::Language => repo.language.as_deref().into_iter().collect(),
+            GroupBy::Topic => repo.topics.iter().map(String::as_str).collect(),
+        };
+        if keys.is_empty() {
+            other.push(repo);
+        }
+        for key in keys {
+            groups.entry(key).or_default().push(repo);
+        }
+    }
+    let sections: Vec<(&str, Vec<&Repo>)> = groups
+        .into_iter()
+        .chain((!other.is_empty()).then_some((OTHER, other)))
+        .collect();
+
+    let mut out = String::new();
+    // writing to a String cannot fail
+    let _ = writeln!(out, "# {}\n", title);
+    let mut anchors: BTreeMap<String, usize> = BTreeMap::new();
+    for (heading, repos) in &sections {
+        // github suffixes repeated anchors, e.g. for C, C# and C++
+        let anchor = anchor(heading);
+        let seen = anchors.entry(anchor.clone()).or_insert(0);
+        let anchor = match *seen {
+            0 => anchor,
+            n => format!("{}-{}", anchor, n),
+        };
+        *seen += 1;
+        let _ = writeln!(out, "- [{}](#{}) ({})", heading, anchor, repos.len());
+    }
+    for (heading, repos) in &sections {
+        let _ = writeln!(out, "\n## {}\n", heading);
+        for repo in repos {
+            let _ = writeln!(out, "{}", entry(repo));
+        }
+    }
+    out
+}
+
+/// list item with link, star badge and description
+fn entry(repo: &Repo) -> String {
+    let name = if repo.full_name.is_empty() {
+        &repo.name
+    } else {
+        &repo.full_name
+    };
+    let mut entry = format!("- [{}]({})", name, repo.url);
+    if !repo.full_name.is_empty() {
+        let _ = write!(
+            entry,
+            " ![Stars](https://img.shields.io/github/stars/{}?style=social)",
+            repo.full_name
+        );
+    }
+    if let Some(description) = repo.description() {
+        let _ = write!(entry, " - {}", description.split_whitespace().collect::<Vec<_>>().join(" "));
+    }
+    entry
+}
+
+/// anchor github generates for a heading
+fn anchor(heading: &str) -> String {
+    heading
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == '-' || *c == '_' || *c == ' ')
+        .map(|c| if c == ' ' { '-' } else { c })
+        .collect()
+}
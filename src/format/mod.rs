@@ -0,0 +1,261 @@
+use chrono::{DateTime, Utc};
+
+use colored::Colorize;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::repo::Repo;
+use crate::source::Source;
+
+mod markdown;
+mod terminal;
+
+pub use markdown::{to_markdown, GroupBy};
+pub use terminal::{
+    write_cache_entries, write_cache_stats, write_comparison, write_diff, write_fetches, write_rate_limits,
+    write_seen, write_stats, write_trend,
+};
+
+use std::io::{self, Write};
+use std::str::FromStr;
+
+/// print repos in term
+pub fn list_repos(repos: &[Repo]) {
+    // stdout going away (e.g. a closed pipe) is not worth reporting
+    let _ = write_list(&mut io::stdout().lock(), repos);
+}
+
+/// write repos in the terminal layout, in the order given
+pub fn write_list(out: &mut impl Write, repos: &[Repo]) -> io::Result<()> {
+    for repo in repos {
+        let description = match repo.description() {
+            Some(description) => description.normal(),
+            None => "No description".dimmed(),
+        };
+        writeln!(
+            out,
+            "{}\n\t{}{}\n\t{}{}\n\t{}{}",
+            repo.name.bold(), "Stars:       ".yellow(), repo.star_count, "Description: ".blue(), description, "URL:         ".green(), repo.url
+        )?;
+        if let Some(starred_at) = repo.starred_at {
+            writeln!(out, "\t{}{}", "Starred:     ".magenta(), starred_at.format("%Y-%m-%d"))?;
+        }
+    }
+    Ok(())
+}
+
+/// serialize repos to a json array
+pub fn to_json(repos: &[Repo]) -> Result<String> {
+    serde_json::to_string(repos).map_err(|err| Error::parse("Could not serialize repos as json", err))
+}
+
+/// read an array written by `to_json`
+pub fn from_json(input: &str) -> Result<Vec<Repo>> {
+    serde_json::from_str(input).map_err(|err| Error::parse("Could not parse json export", err))
+}
+
+/// document written by the toml exporter
+///
+/// toml has no top level arrays, so repos go in a named `[[repos]]` array of tables
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Export {
+    pub meta: ExportMeta,
+    pub repos: Vec<Repo>,
+}
+
+/// where and when an exported list came from
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ExportMeta {
+    /// whose starred list it is, left out for lists from other sources
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// `Source::label` of lists that aren't a starred list, e.g. `org:rust-lang`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    pub fetched_at: DateTime<Utc>,
+    pub count: usize,
+}
+
+/// serialize the starred list of user to a toml document with a `[meta]` table and `[[repos]]`
+/// absent values are left out, toml has no null
+pub fn to_toml(user: &str, fetched_at: DateTime<Utc>, repos: &[Repo]) -> Result<String> {
+    to_toml_for(&Source::Starred(user.to_string()), fetched_at, repos)
+}
+
+/// `to_toml` for a list from any source
+pub fn to_toml_for(source: &Source, fetched_at: DateTime<Utc>, repos: &[Repo]) -> Result<String> {
+    let (user, source) = match source {
+        Source::Starred(user) => (Some(user.clone()), None),
+        source => (None, Some(source.label())),
+    };
+    let export = Export {
+        meta: ExportMeta {
+            user,
+            source,
+            fetched_at,
+            count: repos.len(),
+        },
+        repos: repos.to_vec(),
+    };
+    toml::to_string(&export).map_err(|err| Error::parse("Could not serialize repos as toml", err))
+}
+
+/// read a document written by `to_toml`
+pub fn from_toml(input: &str) -> Result<Export> {
+    toml::from_str(input).map_err(|err| Error::parse("Could not parse toml export", err))
+}
+
+/// a column of the csv and tsv exports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Name,
+    FullName,
+    Owner,
+    Url,
+    Description,
+    Homepage,
+    Language,
+    Topics,
+    License,
+    Stars,
+    Forks,
+    Issues,
+    Fork,
+    Archived,
+    Created,
+    Updated,
+    Pushed,
+    StarredAt,
+}
+
+impl Column {
+    pub const ALL: &'static [Column] = &[
+        Column::Name,
+        Column::FullName,
+        Column::Owner,
+        Column::Url,
+        Column::Description,
+        Column::Homepage,
+        Column::Language,
+        Column::Topics,
+        Column::License,
+        Column::Stars,
+        Column::Forks,
+        Column::Issues,
+        Column::Fork,
+        Column::Archived,
+        Column::Created,
+        Column::Updated,
+        Column::Pushed,
+        Column::StarredAt,
+    ];
+
+    /// columns written when none are selected
+    pub const DEFAULT: &'static [Column] = &[
+        Column::FullName,
+        Column::Url,
+        Column::Description,
+        Column::Language,
+        Column::Stars,
+    ];
+
+    pub fn header(self) -> &'static str {
+        match self {
+            Column::Name => "name",
+            Column::FullName => "full_name",
+            Column::Owner => "owner",
+            Column::Url => "url",
+            Column::Description => "description",
+            Column::Homepage => "homepage",
+            Column::Language => "language",
+            Column::Topics => "topics",
+            Column::License => "license",
+            Column::Stars => "stars",
+            Column::Forks => "forks",
+            Column::Issues => "issues",
+            Column::Fork => "fork",
+            Column::Archived => "archived",
+            Column::Created => "created",
+            Column::Updated => "updated",
+            Column::Pushed => "pushed",
+            Column::StarredAt => "starred_at",
+        }
+    }
+
+    /// value of this column for repo, empty when absent
+    pub fn value(self, repo: &Repo) -> String {
+        let timestamp = |at: Option<DateTime<Utc>>| at.map(|at| at.to_rfc3339()).unwrap_or_default();
+        match self {
+            Column::Name => repo.name.clone(),
+            Column::FullName => repo.full_name.clone(),
+            Column::Owner => repo.owner.login.clone(),
+            Column::Url => repo.url.clone(),
+            Column::Description => repo.description().unwrap_or_default().to_string(),
+            Column::Homepage => repo.homepage().unwrap_or_default().to_string(),
+            Column::Language => repo.language.clone().unwrap_or_default(),
+            Column::Topics => repo.topics.join(" "),
+            Column::License => repo.license_id().unwrap_or_default().to_string(),
+            Column::Stars => repo.star_count.to_string(),
+            Column::Forks => repo.fork_count.to_string(),
+            Column::Issues => repo.open_issue_count.to_string(),
+            Column::Fork => repo.fork.to_string(),
+            Column::Archived => repo.archived.to_string(),
+            Column::Created => timestamp(repo.created_at),
+            Column::Updated => timestamp(repo.updated_at),
+            Column::Pushed => timestamp(repo.pushed_at),
+            Column::StarredAt => timestamp(repo.starred_at),
+        }
+    }
+
+    /// parse comma separated column names, e.g. `name,stars,url`
+    pub fn parse_list(columns: &str) -> Result<Vec<Column>> {
+        columns.split(',').map(str::parse).collect()
+    }
+}
+
+impl FromStr for Column {
+    type Err = Error;
+
+    fn from_str(column: &str) -> Result<Self> {
+        let column = column.trim();
+        match Column::ALL.iter().find(|c| c.header() == column) {
+            Some(c) => Ok(*c),
+            None => Err(Error::invalid(format!(
+                "unknown column {:?}, expected one of {}",
+                column,
+                Column::ALL.iter().map(|c| c.header()).collect::<Vec<_>>().join(", ")
+            ))),
+        }
+    }
+}
+
+/// serialize repos to csv with a header row
+pub fn to_csv(repos: &[Repo], columns: &[Column]) -> Result<String> {
+    to_delimited(repos, columns, b',')
+}
+
+/// serialize repos to tsv with a header row
+pub fn to_tsv(repos: &[Repo], columns: &[Column]) -> Result<String> {
+    to_delimited(repos, columns, b'\t')
+}
+
+/// fields containing the delimiter, quotes or newlines get quoted
+fn to_delimited(repos: &[Repo], columns: &[Column], delimiter: u8) -> Result<String> {
+    let write = || -> std::result::Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(delimiter)
+            .from_writer(Vec::new());
+
+        writer.write_record(columns.iter().map(|c| c.header()))?;
+        for repo in repos {
+            writer.write_record(columns.iter().map(|c| c.value(repo)))?;
+        }
+
+        Ok(String::from_utf8(writer.into_inner()?)?)
+    };
+    write().map_err(|source| Error::Parse {
+        context: "Could not serialize repos as csv".to_string(),
+        source,
+    })
+}
@@ -0,0 +1,292 @@
+use colored::Colorize;
+
+use crate::cache::EntryInfo;
+use crate::compare::Comparison;
+use crate::diff::Diff;
+use crate::duration::format_duration;
+use crate::history::{FetchRecord, Sample, Seen};
+use crate::rate_limit::RateLimits;
+use crate::repo::Repo;
+use crate::stats::{share, Count, Stats};
+
+use std::collections::HashSet;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// most rows of a table `write_stats` writes, the json has all of them
+const TABLE_ROWS: usize = 10;
+
+/// write how similar the compared lists are, then the repos in common and those unique to each user
+pub fn write_comparison(out: &mut impl Write, comparison: &Comparison) -> io::Result<()> {
+    writeln!(
+        out,
+        "{}{:.3} ({} of {} repos in common)",
+        "Similarity:  ".blue(),
+        comparison.similarity,
+        comparison.common.len(),
+        comparison.total
+    )?;
+    if comparison.pairs.len() > 1 {
+        for pair in &comparison.pairs {
+            writeln!(
+                out,
+                "  {} / {}: {:.3} ({} in common)",
+                pair.users.0, pair.users.1, pair.similarity, pair.common
+            )?;
+        }
+    }
+
+    write_section(out, "Starred by everyone", &comparison.common)?;
+    for unique in &comparison.unique {
+        write_section(out, &format!("Only starred by {}", unique.user), &unique.repos)?;
+    }
+    Ok(())
+}
+
+/// write a heading with the number of repos, then one repo per line
+fn write_section(out: &mut impl Write, title: &str, repos: &[Repo]) -> io::Result<()> {
+    writeln!(out, "\n{}", format!("{} ({})", title, repos.len()).bold())?;
+    for repo in repos {
+        writeln!(out, "  {} {}{}", repo.url, "★ ".yellow(), repo.star_count)?;
+    }
+    Ok(())
+}
+
+/// write added, removed and changed repos, one per line, then how many of each
+pub fn write_diff(out: &mut impl Write, diff: &Diff) -> io::Result<()> {
+    for repo in &diff.added {
+        writeln!(out, "{} {} {}{}", "+".green(), repo.url, "★ ".yellow(), repo.star_count)?;
+    }
+    for repo in &diff.removed {
+        writeln!(out, "{} {} {}{}", "-".red(), repo.url, "★ ".yellow(), repo.star_count)?;
+    }
+    for change in &diff.changed {
+        let mut changes = Vec::new();
+        if let Some(stars) = &change.stars {
+            changes.push(format!("stars {} -> {}", stars.before, stars.after));
+        }
+        if change.description.is_some() {
+            changes.push("description changed".to_string());
+        }
+        writeln!(out, "{} {} {}", "~".blue(), change.url, changes.join(", "))?;
+    }
+    writeln!(
+        out,
+        "{}",
+        format!(
+            "{} added, {} removed, {} changed",
+            diff.added.len(),
+            diff.removed.len(),
+            diff.changed.len()
+        )
+        .bold()
+    )
+}
+
+/// write the totals, a table per breakdown and the repos pushed to last
+pub fn write_stats(out: &mut impl Write, stats: &Stats) -> io::Result<()> {
+    writeln!(out, "{}{}", "Repos:       ".blue(), stats.repo_count)?;
+    writeln!(
+        out,
+        "{}{} total, {} median",
+        "Stars:       ".yellow(),
+        stats.star_total,
+        stats.star_median
+    )?;
+    writeln!(
+        out,
+        "{}{} ({:.1}%)",
+        "Archived:    ".magenta(),
+        stats.archived,
+        stats.archived_share * 100.0
+    )?;
+
+    write_counts(out, "Languages", &stats.by_language, stats.repo_count)?;
+    write_counts(out, "Owners", &stats.by_owner, stats.repo_count)?;
+    write_counts(out, "Licenses", &stats.by_license, stats.repo_count)?;
+    write_counts(out, "Topics", &stats.by_topic, stats.repo_count)?;
+    write_counts(out, "Age", &stats.by_age, stats.repo_count)?;
+
+    writeln!(out, "\n{}", "Recently pushed".bold())?;
+    let width = stats
+        .recently_pushed
+        .iter()
+        .map(|repo| repo.full_name.chars().count())
+        .max()
+        .unwrap_or(0);
+    for repo in &stats.recently_pushed {
+        writeln!(
+            out,
+            "  {:<width$}  {}  {}{}",
+            repo.full_name,
+            repo.pushed_at.format("%Y-%m-%d").to_string().dimmed(),
+            "★ ".yellow(),
+            repo.star_count,
+            width = width
+        )?;
+    }
+    Ok(())
+}
+
+/// write a heading, then the most common names with their count and share of total
+fn write_counts(out: &mut impl Write, title: &str, counts: &[Count], total: usize) -> io::Result<()> {
+    writeln!(out, "\n{}", title.bold())?;
+    let shown = &counts[..counts.len().min(TABLE_ROWS)];
+    let width = shown.iter().map(|count| count.name.chars().count()).max().unwrap_or(0);
+    for count in shown {
+        // pad before coloring, escape codes would count towards the width
+        writeln!(
+            out,
+            "  {:<width$}  {}  {}",
+            count.name,
+            format!("{:>5}", count.count).yellow(),
+            format!("{:>5.1}%", share(count.count, total) * 100.0).dimmed(),
+            width = width
+        )?;
+    }
+    if counts.len() > shown.len() {
+        writeln!(out, "  {}", format!("and {} more", counts.len() - shown.len()).dimmed())?;
+    }
+    Ok(())
+}
+
+/// write the counts of a repo at every fetch, with how the stars changed since the one before
+pub fn write_trend(out: &mut impl Write, samples: &[Sample]) -> io::Result<()> {
+    writeln!(
+        out,
+        "{}",
+        format!("{:<17} {:>8} {:>7} {:>7} {:>7}", "FETCHED", "STARS", "CHANGE", "FORKS", "ISSUES").bold()
+    )?;
+    let mut previous = None;
+    for sample in samples {
+        let change = match previous {
+            Some(previous) => format!("{:+}", sample.star_count as i64 - previous as i64),
+            None => String::new(),
+        };
+        writeln!(
+            out,
+            "{:<17} {:>8} {:>7} {:>7} {:>7}",
+            sample.fetched_at.format("%Y-%m-%d %H:%M"),
+            sample.star_count,
+            change,
+            sample.fork_count,
+            sample.open_issue_count
+        )?;
+        previous = Some(sample.star_count);
+    }
+    Ok(())
+}
+
+/// write when each repo was first and last seen, dimming those no longer starred
+pub fn write_seen(out: &mut impl Write, seen: &[Seen]) -> io::Result<()> {
+    writeln!(
+        out,
+        "{}",
+        format!("{:<50} {:<10} {:<10} {:>8}", "REPO", "FIRST SEEN", "LAST SEEN", "STARS").bold()
+    )?;
+    for repo in seen {
+        let line = format!(
+            "{:<50} {:<10} {:<10} {:>8}",
+            repo.url,
+            repo.first_seen.format("%Y-%m-%d"),
+            repo.last_seen.format("%Y-%m-%d"),
+            repo.star_count
+        );
+        if repo.starred {
+            writeln!(out, "{}", line)?;
+        } else {
+            // no longer starred
+            writeln!(out, "{}", line.dimmed())?;
+        }
+    }
+    Ok(())
+}
+
+/// write the size and total stars of a list at every fetch
+pub fn write_fetches(out: &mut impl Write, fetches: &[FetchRecord]) -> io::Result<()> {
+    writeln!(out, "{}", format!("{:<17} {:>6} {:>10}", "FETCHED", "REPOS", "STARS").bold())?;
+    for fetch in fetches {
+        writeln!(
+            out,
+            "{:<17} {:>6} {:>10}",
+            fetch.fetched_at.format("%Y-%m-%d %H:%M"),
+            fetch.repo_count,
+            fetch.star_total
+        )?;
+    }
+    Ok(())
+}
+
+/// write one line per cached list
+pub fn write_cache_entries(out: &mut impl Write, entries: &[EntryInfo]) -> io::Result<()> {
+    writeln!(
+        out,
+        "{}",
+        format!("{:<40} {:<17} {:>8} {:>10} {:>6}", "USER", "FETCHED", "AGE", "SIZE", "REPOS").bold()
+    )?;
+    for entry in entries {
+        writeln!(
+            out,
+            "{:<40} {:<17} {:>8} {:>10} {:>6}",
+            entry.user,
+            entry.fetched_at.format("%Y-%m-%d %H:%M"),
+            format_duration(entry.age()),
+            format_size(entry.size),
+            entry.count
+        )?;
+    }
+    Ok(())
+}
+
+/// write totals over every cached list in dir
+pub fn write_cache_stats(out: &mut impl Write, dir: &Path, entries: &[EntryInfo]) -> io::Result<()> {
+    let users: HashSet<_> = entries.iter().map(|entry| entry.user.to_lowercase()).collect();
+    let ages = entries.iter().map(EntryInfo::age);
+
+    writeln!(out, "{}{}", "Cache dir:   ".blue(), dir.display())?;
+    writeln!(out, "{}{} ({} users)", "Lists:       ".blue(), entries.len(), users.len())?;
+    writeln!(out, "{}{}", "Repos:       ".blue(), entries.iter().map(|entry| entry.count).sum::<usize>())?;
+    writeln!(out, "{}{}", "Size:        ".blue(), format_size(entries.iter().map(|entry| entry.size).sum()))?;
+    if let (Some(oldest), Some(newest)) = (ages.clone().max(), ages.min()) {
+        writeln!(out, "{}{} ago", "Oldest:      ".blue(), format_duration(oldest))?;
+        writeln!(out, "{}{} ago", "Newest:      ".blue(), format_duration(newest))?;
+    }
+    Ok(())
+}
+
+/// format bytes with a binary unit, e.g. `51.2 KiB`
+fn format_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KiB", "MiB", "GiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// write the quota of every api resource, exhausted ones in red
+pub fn write_rate_limits(out: &mut impl Write, limits: &RateLimits) -> io::Result<()> {
+    writeln!(
+        out,
+        "{}",
+        format!("{:<28} {:>7} {:>9} {:>9}", "RESOURCE", "LIMIT", "REMAINING", "RESETS IN").bold()
+    )?;
+    for (resource, limit) in &limits.resources {
+        let remaining = format!("{:>9}", limit.remaining);
+        writeln!(
+            out,
+            "{:<28} {:>7} {} {:>9}",
+            resource,
+            limit.limit,
+            if limit.remaining == 0 { remaining.red() } else { remaining.normal() },
+            format_duration(limit.reset_in())
+        )?;
+    }
+    Ok(())
+}
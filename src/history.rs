@@ -0,0 +1,274 @@
+use chrono::{DateTime, Utc};
+
+use rusqlite::{params, Connection};
+
+use serde::Serialize;
+
+use crate::error::{Error, Result};
+use crate::repo::Repo;
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// env var overriding the dir the history is kept in
+pub const DATA_DIR_ENV: &str = "STARRED_REPOS_DATA_DIR";
+
+const FILE_NAME: &str = "history.sqlite";
+
+/// schema version kept in `PRAGMA user_version`, bump with a migration in `migrate`
+const SCHEMA_VERSION: i64 = 1;
+
+const SCHEMA: &str = "
+    CREATE TABLE fetches (
+        id INTEGER PRIMARY KEY,
+        user TEXT NOT NULL,
+        fetched_at TEXT NOT NULL,
+        repo_count INTEGER NOT NULL
+    );
+    CREATE INDEX fetches_user ON fetches (user, fetched_at);
+
+    CREATE TABLE repos (
+        id INTEGER PRIMARY KEY,
+        url TEXT NOT NULL UNIQUE,
+        full_name TEXT NOT NULL
+    );
+
+    CREATE TABLE samples (
+        fetch_id INTEGER NOT NULL REFERENCES fetches (id) ON DELETE CASCADE,
+        repo_id INTEGER NOT NULL REFERENCES repos (id),
+        star_count INTEGER NOT NULL,
+        fork_count INTEGER NOT NULL,
+        open_issue_count INTEGER NOT NULL,
+        updated_at TEXT,
+        pushed_at TEXT,
+        starred_at TEXT,
+        PRIMARY KEY (fetch_id, repo_id)
+    );
+    CREATE INDEX samples_repo ON samples (repo_id);
+";
+
+/// every starred list fetched from github, to follow lists and star counts over time
+pub struct History {
+    conn: Connection,
+}
+
+/// a recorded fetch of a user's list
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FetchRecord {
+    pub fetched_at: DateTime<Utc>,
+    pub repo_count: usize,
+    /// stars of all repos in the list together
+    pub star_total: u64,
+}
+
+/// the numbers of a repo at one fetch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Sample {
+    pub fetched_at: DateTime<Utc>,
+    pub star_count: u64,
+    pub fork_count: u64,
+    pub open_issue_count: u64,
+}
+
+/// when a repo was first and last in a user's list
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Seen {
+    pub full_name: String,
+    pub url: String,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    /// stars when last seen
+    pub star_count: u64,
+    /// whether it is in the latest recorded list
+    pub starred: bool,
+}
+
+impl History {
+    /// open the history at path, creating it if needed
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|err| Error::io(format!("Could not create history dir {}", dir.display()), err))?;
+        }
+        let conn = Connection::open(path)
+            .map_err(|err| Error::history(format!("Could not open history {}", path.display()), err))?;
+        migrate(&conn)?;
+        Ok(History { conn })
+    }
+
+    /// `history.sqlite` in `$STARRED_REPOS_DATA_DIR`, else `$XDG_DATA_HOME/starred-repos`,
+    /// else the platform data dir (`~/.local/share/starred-repos` on linux)
+    pub fn default_path() -> Result<PathBuf> {
+        if let Some(dir) = env::var_os(DATA_DIR_ENV).filter(|dir| !dir.is_empty()) {
+            return Ok(PathBuf::from(dir).join(FILE_NAME));
+        }
+        let base = env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .or_else(dirs::data_dir)
+            .ok_or_else(|| Error::invalid("Could not find a data directory, set XDG_DATA_HOME or use --no-history"))?;
+        Ok(base.join("starred-repos").join(FILE_NAME))
+    }
+
+    /// record the list of user as fetched at fetched_at
+    pub fn record(&self, user: &str, fetched_at: DateTime<Utc>, repos: &[Repo]) -> Result<()> {
+        let failed = |err| Error::history(format!("Could not record starred list of {}", user), err);
+
+        let tx = self.conn.unchecked_transaction().map_err(failed)?;
+        tx.execute(
+            "INSERT INTO fetches (user, fetched_at, repo_count) VALUES (?1, ?2, ?3)",
+            params![user.to_lowercase(), fetched_at, repos.len()],
+        )
+        .map_err(failed)?;
+        let fetch_id = tx.last_insert_rowid();
+
+        {
+            let mut upsert_repo = tx
+                .prepare_cached(
+                    "INSERT INTO repos (url, full_name) VALUES (?1, ?2)
+                     ON CONFLICT (url) DO UPDATE SET full_name = excluded.full_name
+                     RETURNING id",
+                )
+                .map_err(failed)?;
+            let mut insert_sample = tx
+                .prepare_cached(
+                    "INSERT OR REPLACE INTO samples
+                     (fetch_id, repo_id, star_count, fork_count, open_issue_count, updated_at, pushed_at, starred_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                )
+                .map_err(failed)?;
+
+            for repo in repos {
+                let repo_id: i64 = upsert_repo
+                    .query_row(params![repo.identity(), repo.full_name], |row| row.get(0))
+                    .map_err(failed)?;
+                insert_sample
+                    .execute(params![
+                        fetch_id,
+                        repo_id,
+                        repo.star_count,
+                        repo.fork_count,
+                        repo.open_issue_count,
+                        repo.updated_at,
+                        repo.pushed_at,
+                        repo.starred_at,
+                    ])
+                    .map_err(failed)?;
+            }
+        }
+
+        tx.commit().map_err(failed)
+    }
+
+    /// every recorded fetch of user, oldest first
+    pub fn fetches(&self, user: &str) -> Result<Vec<FetchRecord>> {
+        let failed = |err| Error::history(format!("Could not read history of {}", user), err);
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT f.fetched_at, f.repo_count, COALESCE(SUM(s.star_count), 0)
+                 FROM fetches f LEFT JOIN samples s ON s.fetch_id = f.id
+                 WHERE f.user = ?1
+                 GROUP BY f.id
+                 ORDER BY f.fetched_at",
+            )
+            .map_err(failed)?;
+        let rows = stmt
+            .query_map([user.to_lowercase()], |row| {
+                Ok(FetchRecord {
+                    fetched_at: row.get(0)?,
+                    repo_count: row.get(1)?,
+                    star_total: row.get(2)?,
+                })
+            })
+            .map_err(failed)?;
+        rows.collect::<rusqlite::Result<_>>().map_err(failed)
+    }
+
+    /// star, fork and issue counts of a repo at every fetch it was in, oldest first
+    ///
+    /// repo is a full name like `owner/name` or a url, both ignoring case
+    pub fn trend(&self, repo: &str) -> Result<Vec<Sample>> {
+        let failed = |err| Error::history(format!("Could not read history of {}", repo), err);
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT f.fetched_at, s.star_count, s.fork_count, s.open_issue_count
+                 FROM samples s
+                 JOIN fetches f ON f.id = s.fetch_id
+                 JOIN repos r ON r.id = s.repo_id
+                 WHERE r.url = lower(?1) OR lower(r.full_name) = lower(?1)
+                 ORDER BY f.fetched_at",
+            )
+            .map_err(failed)?;
+        let rows = stmt
+            .query_map([repo], |row| {
+                Ok(Sample {
+                    fetched_at: row.get(0)?,
+                    star_count: row.get(1)?,
+                    fork_count: row.get(2)?,
+                    open_issue_count: row.get(3)?,
+                })
+            })
+            .map_err(failed)?;
+        rows.collect::<rusqlite::Result<_>>().map_err(failed)
+    }
+
+    /// every repo ever recorded in the list of user, most recently added first
+    pub fn seen(&self, user: &str) -> Result<Vec<Seen>> {
+        let failed = |err| Error::history(format!("Could not read history of {}", user), err);
+        let mut stmt = self
+            .conn
+            .prepare(
+                "WITH seen AS (
+                     SELECT s.repo_id, MIN(f.fetched_at) AS first_seen, MAX(f.fetched_at) AS last_seen
+                     FROM samples s JOIN fetches f ON f.id = s.fetch_id
+                     WHERE f.user = ?1
+                     GROUP BY s.repo_id
+                 )
+                 SELECT r.full_name, r.url, seen.first_seen, seen.last_seen, s.star_count,
+                        seen.last_seen = (SELECT MAX(fetched_at) FROM fetches WHERE user = ?1)
+                 FROM seen
+                 JOIN repos r ON r.id = seen.repo_id
+                 JOIN fetches f ON f.user = ?1 AND f.fetched_at = seen.last_seen
+                 JOIN samples s ON s.fetch_id = f.id AND s.repo_id = seen.repo_id
+                 GROUP BY seen.repo_id
+                 ORDER BY seen.first_seen DESC, r.url",
+            )
+            .map_err(failed)?;
+        let rows = stmt
+            .query_map([user.to_lowercase()], |row| {
+                Ok(Seen {
+                    full_name: row.get(0)?,
+                    url: row.get(1)?,
+                    first_seen: row.get(2)?,
+                    last_seen: row.get(3)?,
+                    star_count: row.get(4)?,
+                    starred: row.get(5)?,
+                })
+            })
+            .map_err(failed)?;
+        rows.collect::<rusqlite::Result<_>>().map_err(failed)
+    }
+}
+
+/// create the schema or bring an older one up to date
+fn migrate(conn: &Connection) -> Result<()> {
+    let failed = |err| Error::history("Could not set up history database", err);
+    conn.pragma_update(None, "foreign_keys", true).map_err(failed)?;
+    let version: i64 = conn
+        .pragma_query_value(None, "user_version", |row| row.get(0))
+        .map_err(failed)?;
+    if version > SCHEMA_VERSION {
+        return Err(Error::invalid(format!(
+            "History database has schema version {}, newer than the supported {}",
+            version, SCHEMA_VERSION
+        )));
+    }
+    if version < 1 {
+        conn.execute_batch(SCHEMA).map_err(failed)?;
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION).map_err(failed)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,41 @@
+//! Fetch, cache and render the repositories a GitHub user has starred.
+//!
+//! ```no_run
+//! use github_most_popular::{format, FileCache, StarredClient};
+//!
+//! let client = StarredClient::from_env()?.with_cache(FileCache::new(FileCache::default_dir()?));
+//! let repos = client.starred("hlissner", Some(50))?;
+//! format::list_repos(&repos);
+//! # Ok::<(), github_most_popular::Error>(())
+//! ```
+
+pub mod auth;
+pub mod cache;
+pub mod client;
+pub mod compare;
+pub mod diff;
+pub mod duration;
+pub mod error;
+pub mod filter;
+pub mod format;
+pub mod history;
+pub mod popular;
+pub mod rate_limit;
+pub mod repo;
+pub mod sort;
+pub mod source;
+pub mod stats;
+
+pub use cache::{Cache, CacheEntry, CacheKey, CachedPage, EntryInfo, FileCache, CACHE_VERSION};
+pub use client::{CacheMode, Fetched, StarredClient};
+pub use compare::Comparison;
+pub use diff::{Diff, Snapshot};
+pub use error::{Error, Result};
+pub use filter::Filter;
+pub use history::History;
+pub use popular::Popular;
+pub use rate_limit::{RateLimit, RateLimits};
+pub use repo::Repo;
+pub use sort::{Order, Sort, SortKey};
+pub use source::Source;
+pub use stats::Stats;
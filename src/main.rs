@@ -1,158 +1,551 @@
-use serde::{Deserialize, Serialize};
+use chrono::{DateTime, NaiveDate, Utc};
 
-use anyhow::{ensure, Context, Result};
+use clap::{clap_app, ArgMatches, SubCommand};
 
-use itertools::Itertools;
+use colored::Colorize;
 
-use clap::clap_app;
+use serde::Serialize;
 
-use colored::Colorize;
+use github_most_popular::auth::AuthOptions;
+use github_most_popular::client::{DEFAULT_BACKOFF, DEFAULT_RETRIES};
+use github_most_popular::duration::{format_duration, parse_duration};
+use github_most_popular::format::{Column, GroupBy};
+use github_most_popular::{
+    format, Cache, CacheMode, Comparison, Diff, Error, FileCache, Filter, History, Order, Popular, Repo, Result,
+    Snapshot, Sort, Source, StarredClient, Stats,
+};
 
+use std::error::Error as _;
+use std::fmt::Display;
 use std::fs;
-use std::env;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::str::FromStr;
 
-#[derive(Debug, Deserialize, Serialize)]
-struct Repo {
-    name: String,
-    #[serde(rename = "html_url")]
-    url: String,
-    description: String,
-    #[serde(rename = "stargazers_count")]
-    star_count: u64,
-}
+/// how many repos `popular` lists without --limit
+const DEFAULT_POPULAR_LIMIT: usize = 100;
+
+/// args choosing where repos come from
+const SOURCES: &[&str] = &["USER", "ORG", "OWNED_BY", "REPOS_FILE", "SEARCH"];
+
+/// args writing to a file instead of the terminal
+const EXPORTS: &[&str] = &["JSON", "TOML", "CSV", "TSV", "MARKDOWN"];
+
+const EXIT_CODES: &str = "EXIT CODES:
+    0    success
+    1    invalid arguments
+    2    invalid input
+    3    user, org, repo or cached list not found
+    4    authentication failed
+    5    rate limit exceeded
+    6    network or server error
+    7    parsing or serializing failed
+    8    file system error
+    9    history database error";
 
 fn main() {
     let args = clap_app!(Twitch_cli =>
                          (version: "0.1.0")
                          (author: "Constantin Loew")
                          (@arg USER: -u --user +takes_value "Which user to get the starred repos from")
+                         (@arg ORG: --org +takes_value conflicts_with[USER OWNED_BY REPOS_FILE SEARCH] "List the repos of this organization instead of starred ones")
+                         (@arg OWNED_BY: --("owned-by") +takes_value conflicts_with[USER REPOS_FILE SEARCH] "List the repos this user owns instead of starred ones")
+                         (@arg REPOS_FILE: --("repos-file") +takes_value conflicts_with[USER SEARCH] "List the repos in this file, one owner/name per line")
+                         (@arg SEARCH: --search +takes_value conflicts_with[USER] "List the repos matching this github search query, e.g. \"language:rust stars:>1000\"")
+                         (@arg LIMIT: -l --limit +takes_value "Maximum number of starred repos to fetch")
+                         (@arg LANGUAGE: --language +takes_value "Only repos written in this language")
+                         (@arg TOPIC: --topic +takes_value "Only repos tagged with this topic")
+                         (@arg OWNER: --owner +takes_value "Only repos owned by this user or organization")
+                         (@arg MIN_STARS: --("min-stars") +takes_value "Only repos with at least this many stars")
+                         (@arg STARRED_WITHIN: --("starred-within") +takes_value "Only repos starred within this long, e.g. 30d")
+                         (@arg EXCLUDE_ARCHIVED: --("exclude-archived") "Leave out archived repos")
+                         (@arg EXCLUDE_FORKS: --("exclude-forks") "Leave out forks")
+                         (@arg SORT: -s --sort +takes_value "Comma separated keys to sort by: stars, forks, issues, name, language, created, updated, pushed, starred_at (default: stars)")
+                         (@arg ASC: --asc conflicts_with[DESC] "Sort ascending")
+                         (@arg DESC: --desc "Sort descending")
+                         (@arg MAX_AGE: --("max-age") +takes_value +global "Use cached lists younger than this without asking github, e.g. 30m or 1d (default: 1h)")
+                         (@arg TOKEN_FILE: --("token-file") +takes_value +global "Read the github token from this file")
+                         (@arg ANONYMOUS: --anonymous conflicts_with[TOKEN_FILE] +global "Make requests without a token, even if one is configured")
+                         (@arg WAIT: --wait +global "Wait for the rate limit to reset instead of stopping")
+                         (@arg RETRIES: --retries +takes_value +global "How often to retry requests failing with a server or network error (default: 3)")
+                         (@arg OFFLINE: --offline conflicts_with[REFRESH] +global "Only use cached lists, never touch the network")
+                         (@arg REFRESH: --refresh +global "Fetch lists from github even if they are cached, updating the cache")
+                         (@arg NO_HISTORY: --("no-history") +global "Don't record fetched lists in the history")
+                         (@arg CACHE_DIR: --("cache-dir") +takes_value +global "Directory to cache starred lists in (default: $XDG_CACHE_HOME/starred-repos)")
                          (@arg CLEAR: -c --clear-cache "Clears cache")
                          (@arg JSON: -j --json +takes_value "")
                          (@arg TOML: -t --toml +takes_value "")
+                         (@arg CSV: --csv +takes_value "Write repos as csv to this file")
+                         (@arg TSV: --tsv +takes_value "Write repos as tsv to this file")
+                         (@arg MARKDOWN: --markdown +takes_value "Write repos as a markdown list to this file")
+                         (@arg GROUP_BY: --("group-by") +takes_value possible_value[language topic] "Group the markdown list by language or topic (default: language)")
+                         (@arg COLUMNS: --columns +takes_value "Comma separated csv/tsv columns (default: full_name,url,description,language,stars)")
+                         (@subcommand popular =>
+                          (about: "List the most starred repos on github, optionally of a language, topic or age")
+                          (@arg LANGUAGE: --language +takes_value "Only repos written in this language")
+                          (@arg TOPIC: --topic +takes_value +multiple number_of_values(1) "Only repos tagged with this topic, given once per topic")
+                          (@arg CREATED_AFTER: --("created-after") +takes_value "Only repos created on or after this day, e.g. 2024-01-31")
+                          (@arg MIN_STARS: --("min-stars") +takes_value "Only repos with at least this many stars")
+                          (@arg LIMIT: -l --limit +takes_value "Maximum number of repos to list, github serves at most 1000 (default: 100)")
+                          (@arg JSON: -j --json +takes_value "Write repos as json to this file")
+                          (@arg TOML: -t --toml +takes_value "Write repos as toml to this file")
+                          (@arg CSV: --csv +takes_value "Write repos as csv to this file")
+                          (@arg TSV: --tsv +takes_value "Write repos as tsv to this file")
+                          (@arg MARKDOWN: --markdown +takes_value "Write repos as a markdown list to this file")
+                          (@arg GROUP_BY: --("group-by") +takes_value possible_value[language topic] "Group the markdown list by language or topic (default: language)")
+                          (@arg COLUMNS: --columns +takes_value "Comma separated csv/tsv columns (default: full_name,url,description,language,stars)"))
+                         (@subcommand compare =>
+                          (about: "Compare the starred lists of two or more users")
+                          (@arg USER: -u --user +takes_value +multiple number_of_values(1) +required "User to compare, given once per user")
+                          (@arg JSON: -j --json +takes_value "Write the comparison as json to this file, - for stdout"))
+                         (@subcommand diff =>
+                          (about: "Show what changed in a starred list since a snapshot written by --json or --toml")
+                          (@arg SNAPSHOT: +required "Snapshot to compare with, read as toml if it ends in .toml and as json otherwise")
+                          (@arg USER: -u --user +takes_value "Whose list to compare (default: the user recorded in a toml snapshot)")
+                          (@arg JSON: -j --json +takes_value "Write the diff as json to this file, - for stdout"))
+                         (@subcommand stats =>
+                          (about: "Show what a starred list is made of: languages, owners, licenses, topics, stars and ages")
+                          (@arg USER: -u --user +takes_value +required "Whose list to look at")
+                          (@arg JSON: -j --json +takes_value "Write the stats as json to this file, - for stdout"))
+                         (@subcommand history =>
+                          (about: "Query the history of fetched lists")
+                          (@setting SubcommandRequiredElseHelp)
+                          (@subcommand trend =>
+                           (about: "Star, fork and issue counts of a repo at every fetch")
+                           (@arg REPO: +required "Full name like owner/name or url of the repo")
+                           (@arg JSON: -j --json +takes_value "Write the trend as json to this file, - for stdout"))
+                          (@subcommand seen =>
+                           (about: "When each repo was first and last in the list of a user")
+                           (@arg USER: -u --user +takes_value +required "Whose list to look at")
+                           (@arg JSON: -j --json +takes_value "Write the repos as json to this file, - for stdout"))
+                          (@subcommand user =>
+                           (about: "Size and total stars of the list of a user at every fetch")
+                           (@arg USER: -u --user +takes_value +required "Whose list to look at")
+                           (@arg JSON: -j --json +takes_value "Write the fetches as json to this file, - for stdout")))
+                         (@subcommand cache =>
+                          (about: "Inspect and manage the cache")
+                          (@setting SubcommandRequiredElseHelp)
+                          (@subcommand list => (about: "List cached starred lists with their age, size and repo count"))
+                          (@subcommand clear =>
+                           (about: "Remove cached lists, all of them unless a user is given")
+                           (@arg USER: -u --user +takes_value "Only remove the lists of this user"))
+                          (@subcommand prune =>
+                           (about: "Remove cached lists older than a given age")
+                           (@arg OLDER_THAN: --("older-than") +takes_value +required "Age to remove lists from, e.g. 7d"))
+                          (@subcommand stats => (about: "Show totals over the whole cache"))
+                         )
     )
+    // clap_app! only takes identifiers as subcommand names
+    .subcommand(SubCommand::with_name("rate-limit").about("Show the remaining github api quota"))
+    .after_help(EXIT_CODES)
     .get_matches();
 
+    let result = match args.subcommand() {
+        ("popular", Some(popular_args)) => popular_command(popular_args),
+        ("compare", Some(compare_args)) => compare_command(compare_args),
+        ("diff", Some(diff_args)) => diff_command(diff_args),
+        ("stats", Some(stats_args)) => stats_command(stats_args),
+        ("history", Some(history_args)) => history_command(history_args),
+        ("cache", Some(cache_args)) => cache_command(cache_args),
+        ("rate-limit", Some(rate_limit_args)) => rate_limit_command(rate_limit_args),
+        _ => list_source(&args),
+    };
+    if let Err(err) = result {
+        report(&err);
+        process::exit(err.exit_code());
+    }
+}
+
+/// print err and what caused it to stderr
+fn report(err: &Error) {
+    eprintln!("{} {}", "ERROR:".red().bold(), err);
+    report_causes(err);
+}
+
+/// print err and what caused it to stderr, for failures the run goes on after
+fn warn(err: &Error) {
+    eprintln!("{} {}", "WARNING:".yellow().bold(), err);
+    report_causes(err);
+}
+
+fn report_causes(err: &Error) {
+    let mut source = err.source();
+    while let Some(cause) = source {
+        eprintln!("  caused by: {}", cause);
+        source = cause.source();
+    }
+}
+
+/// fetch, filter, sort and output the repos of the source given in args
+fn list_source(args: &ArgMatches) -> Result<()> {
     if args.is_present("CLEAR") {
-        clear_cache();
-    }
-
-    match args.value_of("USER") {
-        Some(user) => {
-            match get_starred_repos_for_user(&user) {
-                Ok(repos) => {
-                    // if user wants file output silence terminal
-                    if args.value_of("JSON").is_some() || args.value_of("TOML").is_some() {
-                        // write toml to TOML
-                        if let Some(toml_file) = args.value_of("TOML") {
-                            match toml::to_string(&repos) {
-                                Ok(toml_string) => {
-                                    if let Err(err) = fs::write(toml_file, toml_string) {
-                                        println!("Writing to {} failed with {:?}", toml_file, err);
-                                    }
-                                }
-                                Err(err) => println!("Failed serializing toml with {:?}", err),
-                            }
-
-                        }
-                        // write json to JSON
-                        if let Some(json_file) = args.value_of("JSON") {
-                            match serde_json::to_string(&repos) {
-                                Ok(json_string) => {
-                                    if let Err(err) = fs::write(json_file, json_string) {
-                                        println!("Writing to {} failed with {:?}", json_file, err);
-                                    }
-                                }
-                                Err(err) => println!("Failed serializing json with {:?}", err),
-                            }
-
-                        }
-                    } else { // else print repos to terminal
-                        list_repos(&repos);
-                    }
-
-                },
-                Err(err) => println!("ERROR: {:?}", err),
-            }
+        FileCache::new(cache_dir(args)?).clear()?;
+        // clearing is all that was asked for
+        if !SOURCES.iter().any(|source| args.is_present(source)) {
+            return Ok(());
+        }
+    }
+
+    let limit = parse_arg::<usize>(args, "LIMIT", "limit")?;
+    let min_stars = parse_arg::<u64>(args, "MIN_STARS", "minimum stars")?;
+
+    let starred_after = match args.value_of("STARRED_WITHIN") {
+        Some(within) => {
+            let within = parse_duration(within)?;
+            let within = chrono::Duration::from_std(within).map_err(|err| invalid("starred within", err))?;
+            let after = Utc::now()
+                .checked_sub_signed(within)
+                .ok_or_else(|| invalid("starred within", "too long ago to be a date"))?;
+            Some(after)
+        }
+        None => None,
+    };
+
+    let filter = Filter {
+        language: args.value_of("LANGUAGE").map(String::from),
+        topic: args.value_of("TOPIC").map(String::from),
+        owner: args.value_of("OWNER").map(String::from),
+        min_stars,
+        starred_after,
+        exclude_archived: args.is_present("EXCLUDE_ARCHIVED"),
+        exclude_forks: args.is_present("EXCLUDE_FORKS"),
+    };
+
+    let order = if args.is_present("ASC") {
+        Some(Order::Asc)
+    } else if args.is_present("DESC") {
+        Some(Order::Desc)
+    } else {
+        None
+    };
+    let sort = match args.value_of("SORT") {
+        Some(keys) => Sort::parse(keys, order)?,
+        None => Sort { order, ..Sort::default() },
+    };
+
+    let output = Output::from_args(args)?;
+
+    let client = client(args)?;
+    let source = source(args)?;
+    let fetched = client.fetch(&source, limit)?;
+    let mut repos = filter.apply(fetched.repos);
+    sort.apply(&mut repos);
+
+    output.write(&source, fetched.fetched_at, &repos)
+}
+
+/// search the most starred repos matching the filters in args, in the order github ranks them
+fn popular_command(args: &ArgMatches) -> Result<()> {
+    let popular = Popular {
+        language: args.value_of("LANGUAGE").map(String::from),
+        topics: args.values_of("TOPIC").map(|topics| topics.map(String::from).collect()).unwrap_or_default(),
+        created_after: parse_arg::<NaiveDate>(args, "CREATED_AFTER", "creation date")?,
+        min_stars: parse_arg::<u64>(args, "MIN_STARS", "minimum stars")?,
+    };
+    let limit = parse_arg::<usize>(args, "LIMIT", "limit")?.unwrap_or(DEFAULT_POPULAR_LIMIT);
+    let output = Output::from_args(args)?;
+
+    let source = popular.source();
+    let fetched = client(args)?.fetch(&source, Some(limit))?;
+    output.write(&source, fetched.fetched_at, &fetched.repos)
+}
+
+/// where repos go, the terminal unless an export file is given
+struct Output<'a> {
+    args: &'a ArgMatches<'a>,
+    columns: Vec<Column>,
+    group_by: GroupBy,
+}
+
+impl<'a> Output<'a> {
+    /// read the export options of args, before fetching so bad ones fail early
+    fn from_args(args: &'a ArgMatches<'a>) -> Result<Self> {
+        let columns = match args.value_of("COLUMNS") {
+            Some(columns) => Column::parse_list(columns)?,
+            None => Column::DEFAULT.to_vec(),
+        };
+        let group_by = parse_arg::<GroupBy>(args, "GROUP_BY", "grouping")?.unwrap_or_default();
+        Ok(Output { args, columns, group_by })
+    }
+
+    fn write(&self, source: &Source, fetched_at: DateTime<Utc>, repos: &[Repo]) -> Result<()> {
+        let args = self.args;
+        // if user wants file output silence terminal
+        if !EXPORTS.iter().any(|export| args.is_present(export)) {
+            format::list_repos(repos);
+            return Ok(());
+        }
+        if let Some(toml_file) = args.value_of("TOML") {
+            export(toml_file, format::to_toml_for(source, fetched_at, repos)?)?;
+        }
+        if let Some(json_file) = args.value_of("JSON") {
+            export(json_file, format::to_json(repos)?)?;
+        }
+        if let Some(csv_file) = args.value_of("CSV") {
+            export(csv_file, format::to_csv(repos, &self.columns)?)?;
+        }
+        if let Some(tsv_file) = args.value_of("TSV") {
+            export(tsv_file, format::to_tsv(repos, &self.columns)?)?;
         }
-        None => println!("No user was specified"),
+        if let Some(markdown_file) = args.value_of("MARKDOWN") {
+            export(markdown_file, format::to_markdown(&source.title(), repos, self.group_by))?;
+        }
+        Ok(())
+    }
+}
+
+/// where to get repos from, the starred list of --user unless another source is given
+fn source(args: &ArgMatches) -> Result<Source> {
+    let source = if let Some(org) = args.value_of("ORG") {
+        Source::Org(org.to_string())
+    } else if let Some(user) = args.value_of("OWNED_BY") {
+        Source::OwnedBy(user.to_string())
+    } else if let Some(file) = args.value_of("REPOS_FILE") {
+        Source::read_repos_file(Path::new(file))?
+    } else if let Some(query) = args.value_of("SEARCH") {
+        Source::Search(query.to_string())
+    } else if let Some(user) = args.value_of("USER") {
+        Source::Starred(user.to_string())
+    } else {
+        return Err(Error::Invalid(
+            "No user was specified, use --user or one of --org, --owned-by, --repos-file, --search".to_string(),
+        ));
+    };
+    Ok(source)
+}
+
+/// client with the cache, auth and retry behaviour asked for in args
+fn client(args: &ArgMatches) -> Result<StarredClient> {
+    let cache_mode = if args.is_present("OFFLINE") {
+        CacheMode::Offline
+    } else if args.is_present("REFRESH") {
+        CacheMode::Refresh
+    } else {
+        CacheMode::Normal
+    };
+    let retries = parse_arg::<u32>(args, "RETRIES", "retries")?.unwrap_or(DEFAULT_RETRIES);
+
+    let mut client = StarredClient::from_env_with(&auth_options(args))?
+        .with_cache(FileCache::new(cache_dir(args)?))
+        .with_cache_mode(cache_mode)
+        .with_retries(retries, DEFAULT_BACKOFF)
+        .with_wait_for_rate_limit(args.is_present("WAIT"))
+        .on_rate_limit_wait(|wait| eprintln!("Rate limited by github, waiting {}", format_duration(wait)))
+        .on_history_error(warn);
+    if let Some(max_age) = args.value_of("MAX_AGE") {
+        client = client.with_max_age(parse_duration(max_age)?);
     }
+    // the history is a nice to have, listing works without it
+    if !args.is_present("NO_HISTORY") {
+        match History::default_path().and_then(|path| History::open(&path)) {
+            Ok(history) => client = client.with_history(history),
+            Err(err) => warn(&err),
+        }
+    }
+    Ok(client)
+}
+
+/// parse the value of arg if given, naming it `what` in the error
+fn parse_arg<T>(args: &ArgMatches, arg: &str, what: &str) -> Result<Option<T>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    args.value_of(arg)
+        .map(|value| value.parse().map_err(|err| invalid(what, err)))
+        .transpose()
+}
+
+fn invalid(what: &str, err: impl Display) -> Error {
+    Error::Invalid(format!("Invalid {}: {}", what, err))
 }
 
-/// make request to github api for user
-fn get_starred_repos_for_user(user: &str) -> Result<Vec<Repo>> {
-    if let Some(cached_response) = get_cache(user) {
-        let repos: Vec<Repo> = serde_json::from_str(&cached_response)?;
-        return Ok(repos);
+/// fetch the list of every --user and show what they have in common
+fn compare_command(args: &ArgMatches) -> Result<()> {
+    let users: Vec<&str> = args.values_of("USER").map(Iterator::collect).unwrap_or_default();
+    if users.len() < 2 {
+        return Err(Error::Invalid("Comparing needs at least two users".to_string()));
     }
 
-    let client = reqwest::blocking::Client::new();
-    let url = format!("https://api.github.com/users/{}/starred?per_page=10", user);
+    let client = client(args)?;
+    let lists = users
+        .iter()
+        .map(|user| Ok((user.to_string(), client.starred(user, None)?)))
+        .collect::<Result<Vec<_>>>()?;
+    let comparison = Comparison::new(lists);
 
-    let access_token =
-        env::var("GITHUB_ACCESS").context("Could not get access token, is TWITCH_ACCESS set?")?;
+    match args.value_of("JSON") {
+        Some(target) => write_json(target, comparison.to_json()?)?,
+        None => print(|out| format::write_comparison(out, &comparison)),
+    }
+    Ok(())
+}
 
-    let req = client
-        .get(&url)
-        .header("User-Agent", "starred-repos")
-        .header(reqwest::header::ACCEPT, "application/vnd.github+json")
-        .header(reqwest::header::AUTHORIZATION, &format!("Bearer {}", access_token));
+/// diff the current list of a user against a snapshot
+fn diff_command(args: &ArgMatches) -> Result<()> {
+    let snapshot = Snapshot::load(Path::new(args.value_of("SNAPSHOT").unwrap_or_default()))?;
+    let user = match (args.value_of("USER"), snapshot.user.as_deref(), &snapshot.source) {
+        (Some(user), _, _) | (None, Some(user), _) => user,
+        (None, None, Some(source)) => {
+            return Err(Error::Invalid(format!(
+                "The snapshot holds {}, not a starred list, use --user to compare it with the list of a user",
+                source
+            )))
+        }
+        (None, None, None) => {
+            return Err(Error::Invalid("No user was specified and the snapshot does not record one".to_string()))
+        }
+    };
 
-    let res = req.send().context("Could not connect to github api")?;
+    let repos = client(args)?.starred(user, None)?;
+    let diff = Diff::new(&snapshot.repos, &repos);
 
-    ensure!(
-        res.status().is_success(),
-        "{} returned error with status {}",
-        url,
-        res.status()
-    );
+    match args.value_of("JSON") {
+        Some(target) => write_json(target, diff.to_json()?)?,
+        None => print(|out| format::write_diff(out, &diff)),
+    }
+    Ok(())
+}
 
-    let response_text = res.text()?;
-    write_cache(user, &response_text);
+/// show what the list of a user is made of
+fn stats_command(args: &ArgMatches) -> Result<()> {
+    let user = args.value_of("USER").unwrap_or_default();
+    let repos = client(args)?.starred(user, None)?;
+    let stats = Stats::new(&repos, Utc::now());
 
-    let repos: Vec<Repo> = serde_json::from_str(&response_text)?;
-    Ok(repos)
+    match args.value_of("JSON") {
+        Some(target) => write_json(target, stats.to_json()?)?,
+        None => print(|out| format::write_stats(out, &stats)),
+    }
+    Ok(())
 }
 
-/// write cache for user
-fn write_cache(user: &str, response: &str) {
-    if fs::read_dir("cache").is_err() {
-        if let Err(err) = fs::create_dir("cache") {
-            println!("Error creating cache: {:?}", err);
+/// run a history subcommand
+fn history_command(args: &ArgMatches) -> Result<()> {
+    let (command, args) = match args.subcommand() {
+        (command, Some(args)) => (command, args),
+        _ => return Ok(()),
+    };
+    let history = History::open(&History::default_path()?)?;
+
+    match command {
+        "trend" => {
+            let repo = args.value_of("REPO").unwrap_or_default();
+            let samples = history.trend(repo)?;
+            match args.value_of("JSON") {
+                Some(target) => write_json(target, to_json(&samples, "trend")?)?,
+                None if samples.is_empty() => println!("No history recorded for {}", repo),
+                None => print(|out| format::write_trend(out, &samples)),
+            }
+        }
+        "seen" => {
+            let user = args.value_of("USER").unwrap_or_default();
+            let seen = history.seen(user)?;
+            match args.value_of("JSON") {
+                Some(target) => write_json(target, to_json(&seen, "seen repos")?)?,
+                None if seen.is_empty() => println!("No history recorded for {}", user),
+                None => print(|out| format::write_seen(out, &seen)),
+            }
+        }
+        "user" => {
+            let user = args.value_of("USER").unwrap_or_default();
+            let fetches = history.fetches(user)?;
+            match args.value_of("JSON") {
+                Some(target) => write_json(target, to_json(&fetches, "fetches")?)?,
+                None if fetches.is_empty() => println!("No history recorded for {}", user),
+                None => print(|out| format::write_fetches(out, &fetches)),
+            }
         }
+        _ => (),
     }
-    match fs::write(format!("cache/{}", user), response) {
-        Ok(_) => (),
-        Err(err) => println!("Error writing to cache: {:?}", err),
+    Ok(())
+}
+
+/// run a cache subcommand
+fn cache_command(args: &ArgMatches) -> Result<()> {
+    let (command, args) = match args.subcommand() {
+        (command, Some(args)) => (command, args),
+        _ => return Ok(()),
+    };
+    let cache = FileCache::new(cache_dir(args)?);
+
+    match command {
+        "list" => {
+            let entries = cache.entries()?;
+            print(|out| format::write_cache_entries(out, &entries));
+        }
+        "clear" => match args.value_of("USER") {
+            Some(user) => println!("Removed {} cached lists of {}", cache.clear_user(user)?, user),
+            None => println!("Removed {} cached lists", cache.clear()?),
+        },
+        "prune" => {
+            let older_than = parse_duration(args.value_of("OLDER_THAN").unwrap_or_default())?;
+            println!("Removed {} cached lists", cache.prune(older_than)?);
+        }
+        "stats" => {
+            let entries = cache.entries()?;
+            print(|out| format::write_cache_stats(out, cache.dir(), &entries));
+        }
+        _ => (),
     }
+    Ok(())
 }
 
-/// get cache for user
-/// returns None on cache miss
-fn get_cache(user: &str) -> Option<String> {
-    match fs::read_to_string(format!("cache/{}", user)) {
-        Ok(cached_response) => Some(cached_response),
-        Err(_) => None
+/// print the quota of every api resource
+fn rate_limit_command(args: &ArgMatches) -> Result<()> {
+    if args.is_present("OFFLINE") {
+        return Err(Error::Invalid("The rate limit can only be asked for online, not with --offline".to_string()));
     }
+    let limits = StarredClient::from_env_with(&auth_options(args))?.rate_limit()?;
+    print(|out| format::write_rate_limits(out, &limits));
+    Ok(())
 }
 
-/// clear cache by deleting dir
-fn clear_cache() {
-    if let Err(err) = fs::remove_dir_all("cache") {
-        println!("Failed clearing cache with {:?}", err);
+/// write to stdout with one of the `format::write_*` functions
+fn print(write: impl FnOnce(&mut io::StdoutLock<'static>) -> io::Result<()>) {
+    // stdout going away (e.g. a closed pipe) is not worth reporting
+    let _ = write(&mut io::stdout().lock());
+}
+
+/// where to look for a token according to --token-file and --anonymous
+fn auth_options(args: &ArgMatches) -> AuthOptions {
+    AuthOptions {
+        token_file: args.value_of("TOKEN_FILE").map(PathBuf::from),
+        anonymous: args.is_present("ANONYMOUS"),
     }
 }
 
-/// print repos in term
-fn list_repos(repos: &[Repo]) {
-    for repo in repos
-        .into_iter()
-        .sorted_by(|a, b| Ord::cmp(&b.star_count, &a.star_count))
-    {
-        println!(
-            "{}\n\t{}{}\n\t{}{}\n\t{}{}",
-            repo.name.bold(), format!("Stars:       ").yellow(), repo.star_count, format!("Description: ").blue(), repo.description, format!("URL:         ").green(), repo.url
-        );
+/// cache dir from --cache-dir or the default location
+fn cache_dir(args: &ArgMatches) -> Result<PathBuf> {
+    match args.value_of("CACHE_DIR") {
+        Some(dir) => Ok(dir.into()),
+        None => FileCache::default_dir(),
     }
+}
 
+fn to_json(value: &impl Serialize, what: &str) -> Result<String> {
+    serde_json::to_string(value).map_err(|err| Error::Parse {
+        context: format!("Could not serialize {} as json", what),
+        source: Box::new(err),
+    })
+}
+
+/// write json to the file target, or stdout for `-`
+fn write_json(target: &str, json: String) -> Result<()> {
+    match target {
+        "-" => {
+            println!("{}", json);
+            Ok(())
+        }
+        file => export(file, json),
+    }
+}
 
+/// write serialized repos to file
+fn export(file: &str, contents: String) -> Result<()> {
+    fs::write(file, contents).map_err(|err| Error::Io {
+        context: format!("Could not write {}", file),
+        source: err,
+    })
 }
@@ -0,0 +1,49 @@
+use chrono::NaiveDate;
+
+use crate::source::Source;
+
+/// the most starred repos on github, narrowed down by language, topics, age and stars
+///
+/// turned into a github search query, results come sorted by stars
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Popular {
+    /// primary language, e.g. `rust`
+    pub language: Option<String>,
+    /// topics the repos need to have, all of them
+    pub topics: Vec<String>,
+    /// created on or after this day
+    pub created_after: Option<NaiveDate>,
+    /// at least this many stars, 1 if not set
+    pub min_stars: Option<u64>,
+}
+
+impl Popular {
+    /// search query like `stars:>=1000 language:rust topic:cli created:>=2024-01-01`
+    pub fn query(&self) -> String {
+        let mut query = vec![format!("stars:>={}", self.min_stars.unwrap_or(1))];
+        if let Some(language) = &self.language {
+            query.push(qualifier("language", language));
+        }
+        for topic in &self.topics {
+            query.push(qualifier("topic", topic));
+        }
+        if let Some(created_after) = self.created_after {
+            query.push(format!("created:>={}", created_after.format("%Y-%m-%d")));
+        }
+        query.join(" ")
+    }
+
+    pub fn source(&self) -> Source {
+        Source::Search(self.query())
+    }
+}
+
+/// `key:value`, quoting values with spaces like `language:"visual basic"`
+fn qualifier(key: &str, value: &str) -> String {
+    let value = value.trim();
+    if value.contains(char::is_whitespace) {
+        format!("{}:\"{}\"", key, value.replace('"', ""))
+    } else {
+        format!("{}:{}", key, value)
+    }
+}
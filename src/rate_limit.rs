@@ -0,0 +1,45 @@
+use chrono::{DateTime, TimeZone, Utc};
+
+use reqwest::header::HeaderMap;
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// quota of one github api resource
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RateLimit {
+    pub limit: u64,
+    pub remaining: u64,
+    #[serde(default)]
+    pub used: u64,
+    /// when the quota is refilled
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub reset: DateTime<Utc>,
+}
+
+/// quotas of every resource as returned by `/rate_limit`, e.g. `core` and `search`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RateLimits {
+    pub resources: BTreeMap<String, RateLimit>,
+}
+
+impl RateLimit {
+    /// read the `X-RateLimit-*` headers github sends with every response
+    pub(crate) fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let number = |name: &str| headers.get(name)?.to_str().ok()?.trim().parse::<u64>().ok();
+        let reset = number("x-ratelimit-reset")?;
+        Some(RateLimit {
+            limit: number("x-ratelimit-limit")?,
+            remaining: number("x-ratelimit-remaining")?,
+            used: number("x-ratelimit-used").unwrap_or_default(),
+            reset: Utc.timestamp_opt(reset as i64, 0).single()?,
+        })
+    }
+
+    /// time until the quota is refilled, zero if that already happened
+    pub fn reset_in(&self) -> Duration {
+        (self.reset - Utc::now()).to_std().unwrap_or_default()
+    }
+}
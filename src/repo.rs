@@ -0,0 +1,136 @@
+use chrono::{DateTime, Utc};
+
+use serde::{Deserialize, Serialize};
+
+/// a repository as returned by the github api
+///
+/// fields github sends as `null` or leaves out are `None` or empty,
+/// so older exports with only a few fields still read back in
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Repo {
+    #[serde(default)]
+    pub id: u64,
+    pub name: String,
+    /// `owner/name`
+    #[serde(default)]
+    pub full_name: String,
+    #[serde(rename = "html_url")]
+    pub url: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub homepage: Option<String>,
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub topics: Vec<String>,
+    #[serde(rename = "stargazers_count", default)]
+    pub star_count: u64,
+    #[serde(rename = "forks_count", default)]
+    pub fork_count: u64,
+    #[serde(rename = "open_issues_count", default)]
+    pub open_issue_count: u64,
+    /// whether this repo is a fork of another
+    #[serde(default)]
+    pub fork: bool,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub default_branch: Option<String>,
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+    /// last push to any branch, `None` for empty repos
+    #[serde(default)]
+    pub pushed_at: Option<DateTime<Utc>>,
+    /// when the user starred this repo, only known for starred lists
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub starred_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub owner: Owner,
+    #[serde(default)]
+    pub license: Option<License>,
+}
+
+/// user or organization owning a repo
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct Owner {
+    pub login: String,
+    #[serde(default)]
+    pub id: u64,
+    #[serde(default)]
+    pub html_url: String,
+    /// `User` or `Organization`
+    #[serde(rename = "type", default)]
+    pub kind: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct License {
+    /// lowercase license key, e.g. `mit`
+    pub key: String,
+    pub name: String,
+    /// spdx id, `NOASSERTION` for licenses github could not identify
+    #[serde(default)]
+    pub spdx_id: Option<String>,
+}
+
+/// an entry of a starred list
+///
+/// the star+json media type wraps each repo with the time it was starred,
+/// plain json (and caches written before) lists bare repos
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub(crate) enum StarredEntry {
+    Timestamped {
+        starred_at: DateTime<Utc>,
+        repo: Box<Repo>,
+    },
+    Plain(Box<Repo>),
+}
+
+impl From<StarredEntry> for Repo {
+    fn from(entry: StarredEntry) -> Self {
+        match entry {
+            StarredEntry::Timestamped { starred_at, repo } => Repo {
+                starred_at: Some(starred_at),
+                ..*repo
+            },
+            StarredEntry::Plain(repo) => *repo,
+        }
+    }
+}
+
+/// parse starred list entries with or without star timestamps
+pub(crate) fn parse_starred(items: Vec<serde_json::Value>) -> serde_json::Result<Vec<Repo>> {
+    items
+        .into_iter()
+        .map(|item| Ok(Repo::from(serde_json::from_value::<StarredEntry>(item)?)))
+        .collect()
+}
+
+impl Repo {
+    /// description, treating an empty one as absent
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref().filter(|d| !d.trim().is_empty())
+    }
+
+    /// homepage, treating an empty one as absent
+    pub fn homepage(&self) -> Option<&str> {
+        self.homepage.as_deref().filter(|h| !h.trim().is_empty())
+    }
+
+    /// spdx id of the license, falling back to its key
+    pub fn license_id(&self) -> Option<&str> {
+        self.license
+            .as_ref()
+            .map(|license| license.spdx_id.as_deref().unwrap_or(&license.key))
+    }
+
+    /// what identifies the repo across lists, its url ignoring case
+    /// ids are left out of some payloads, the url never is
+    pub(crate) fn identity(&self) -> String {
+        self.url.to_lowercase()
+    }
+}
@@ -0,0 +1,142 @@
+use crate::error::{Error, Result};
+
+use crate::repo::Repo;
+
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+/// field to sort repos by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    Stars,
+    Forks,
+    Issues,
+    Name,
+    Language,
+    Created,
+    Updated,
+    Pushed,
+    StarredAt,
+}
+
+impl SortKey {
+    /// counts and dates put the biggest/newest first, names sort alphabetically
+    pub fn default_order(self) -> Order {
+        match self {
+            SortKey::Name | SortKey::Language => Order::Asc,
+            _ => Order::Desc,
+        }
+    }
+
+    fn compare(self, a: &Repo, b: &Repo, order: Order) -> Ordering {
+        match self {
+            SortKey::Stars => order.apply(a.star_count.cmp(&b.star_count)),
+            SortKey::Forks => order.apply(a.fork_count.cmp(&b.fork_count)),
+            SortKey::Issues => order.apply(a.open_issue_count.cmp(&b.open_issue_count)),
+            SortKey::Name => order.apply(
+                a.name
+                    .to_lowercase()
+                    .cmp(&b.name.to_lowercase())
+                    .then_with(|| a.full_name.cmp(&b.full_name)),
+            ),
+            SortKey::Language => compare_present(
+                a.language.as_ref().map(|l| l.to_lowercase()),
+                b.language.as_ref().map(|l| l.to_lowercase()),
+                order,
+            ),
+            SortKey::Created => compare_present(a.created_at, b.created_at, order),
+            SortKey::Updated => compare_present(a.updated_at, b.updated_at, order),
+            SortKey::Pushed => compare_present(a.pushed_at, b.pushed_at, order),
+            SortKey::StarredAt => compare_present(a.starred_at, b.starred_at, order),
+        }
+    }
+}
+
+impl FromStr for SortKey {
+    type Err = Error;
+
+    fn from_str(key: &str) -> Result<Self> {
+        Ok(match key.trim() {
+            "stars" => SortKey::Stars,
+            "forks" => SortKey::Forks,
+            "issues" => SortKey::Issues,
+            "name" => SortKey::Name,
+            "language" => SortKey::Language,
+            "created" => SortKey::Created,
+            "updated" => SortKey::Updated,
+            "pushed" => SortKey::Pushed,
+            "starred_at" => SortKey::StarredAt,
+            other => {
+                return Err(Error::invalid(format!(
+                    "unknown sort key {:?}, expected one of stars, forks, issues, name, language, created, updated, pushed, starred_at",
+                    other
+                )))
+            }
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    Asc,
+    Desc,
+}
+
+impl Order {
+    fn apply(self, ordering: Ordering) -> Ordering {
+        match self {
+            Order::Asc => ordering,
+            Order::Desc => ordering.reverse(),
+        }
+    }
+}
+
+/// sort keys applied in turn, later keys break ties of earlier ones
+#[derive(Debug, Clone)]
+pub struct Sort {
+    pub keys: Vec<SortKey>,
+    /// direction for every key, `None` uses each key's default order
+    pub order: Option<Order>,
+}
+
+impl Default for Sort {
+    /// most stars first
+    fn default() -> Self {
+        Sort {
+            keys: vec![SortKey::Stars],
+            order: None,
+        }
+    }
+}
+
+impl Sort {
+    /// parse comma separated keys, e.g. `language,stars`
+    pub fn parse(keys: &str, order: Option<Order>) -> Result<Self> {
+        let keys = keys
+            .split(',')
+            .map(str::parse)
+            .collect::<Result<Vec<SortKey>>>()?;
+        Ok(Sort { keys, order })
+    }
+
+    pub fn compare(&self, a: &Repo, b: &Repo) -> Ordering {
+        self.keys.iter().fold(Ordering::Equal, |ordering, key| {
+            ordering.then_with(|| key.compare(a, b, self.order.unwrap_or(key.default_order())))
+        })
+    }
+
+    /// sort repos in place, repos comparing equal keep their order
+    pub fn apply(&self, repos: &mut [Repo]) {
+        repos.sort_by(|a, b| self.compare(a, b));
+    }
+}
+
+/// compare optional values, absent ones go last in either direction
+fn compare_present<T: Ord>(a: Option<T>, b: Option<T>, order: Order) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => order.apply(a.cmp(&b)),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
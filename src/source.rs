@@ -0,0 +1,111 @@
+use crate::cache::validate_login;
+use crate::error::{Error, Result};
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// where a list of repos comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// repos a user starred
+    Starred(String),
+    /// repos of an organization
+    Org(String),
+    /// repos a user owns
+    OwnedBy(String),
+    /// repos given by full name, e.g. `rust-lang/rust`
+    Repos(Vec<String>),
+    /// results of a github repository search, e.g. `language:rust stars:>1000`, most stars first
+    Search(String),
+}
+
+impl Source {
+    /// read one `owner/name` per line, skipping blank lines and `#` comments,
+    /// github urls like `https://github.com/owner/name` work too
+    pub fn repos_from_list(list: &str) -> Result<Self> {
+        let mut repos = Vec::new();
+        for line in list.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let repo = line
+                .strip_prefix("https://github.com/")
+                .or_else(|| line.strip_prefix("http://github.com/"))
+                .unwrap_or(line)
+                .trim_end_matches('/')
+                .trim_end_matches(".git");
+            validate_full_name(repo)?;
+            repos.push(repo.to_string());
+        }
+        Ok(Source::Repos(repos))
+    }
+
+    /// `repos_from_list` on the contents of file
+    pub fn read_repos_file(path: &Path) -> Result<Self> {
+        let list = fs::read_to_string(path)
+            .map_err(|err| Error::io(format!("Could not read repos file {}", path.display()), err))?;
+        Source::repos_from_list(&list)
+    }
+
+    /// check the logins and names urls get built from
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            Source::Starred(login) | Source::Org(login) | Source::OwnedBy(login) => validate_login(login),
+            Source::Repos(repos) => repos.iter().try_for_each(|repo| validate_full_name(repo)),
+            Source::Search(query) if query.trim().is_empty() => Err(Error::invalid("The search query is empty")),
+            Source::Search(_) => Ok(()),
+        }
+    }
+
+    /// what the list is stored under in the cache and history, just the login for starred lists
+    pub fn label(&self) -> String {
+        match self {
+            Source::Starred(user) => user.clone(),
+            Source::Org(org) => format!("org:{}", org),
+            Source::OwnedBy(user) => format!("owned:{}", user),
+            Source::Repos(_) => "list".to_string(),
+            Source::Search(query) => format!("search:{}", query),
+        }
+    }
+
+    /// heading for exports, e.g. `Repos starred by octocat`
+    pub fn title(&self) -> String {
+        match self {
+            Source::Starred(user) => format!("Repos starred by {}", user),
+            Source::Org(org) => format!("Repos of {}", org),
+            Source::OwnedBy(user) => format!("Repos owned by {}", user),
+            Source::Repos(_) => "Repos".to_string(),
+            Source::Search(query) => format!("Repos matching {}", query),
+        }
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Starred(user) => write!(f, "starred list for {}", user),
+            Source::Org(org) => write!(f, "repos of organization {}", org),
+            Source::OwnedBy(user) => write!(f, "repos owned by {}", user),
+            Source::Repos(repos) => write!(f, "list of {} repos", repos.len()),
+            Source::Search(query) => write!(f, "search results for {:?}", query),
+        }
+    }
+}
+
+/// check repo is `owner/name` with a valid login and a name of
+/// 1 to 100 ascii alphanumerics, `.`, `_` or `-`
+pub fn validate_full_name(repo: &str) -> Result<()> {
+    let invalid = || Error::invalid(format!("{:?} is not a valid repository, expected owner/name", repo));
+    let (owner, name) = repo.split_once('/').ok_or_else(invalid)?;
+    validate_login(owner).map_err(|_| invalid())?;
+    let valid_name = !name.is_empty()
+        && name.len() <= 100
+        && name != "."
+        && name != ".."
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'));
+    if !valid_name {
+        return Err(invalid());
+    }
+    Ok(())
+}
@@ -0,0 +1,173 @@
+use chrono::{DateTime, Utc};
+
+use serde::Serialize;
+
+use crate::error::{Error, Result};
+use crate::repo::Repo;
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+/// how many repos `recently_pushed` holds
+pub const RECENTLY_PUSHED: usize = 10;
+
+/// label for repos without a language or license
+pub const NONE: &str = "none";
+
+/// upper bounds in years of the age buckets, the last one is open ended
+const AGE_BUCKETS: &[(i64, &str)] = &[
+    (1, "under 1 year"),
+    (2, "1 to 2 years"),
+    (5, "2 to 5 years"),
+    (10, "5 to 10 years"),
+    (i64::MAX, "10 years or more"),
+];
+
+/// what a list of repos is made of
+#[derive(Debug, Clone, Serialize)]
+pub struct Stats {
+    pub repo_count: usize,
+    pub star_total: u64,
+    /// middle star count, the mean of the two middle ones for an even number of repos
+    pub star_median: f64,
+    /// most common first, like every count below
+    pub by_language: Vec<Count>,
+    pub by_owner: Vec<Count>,
+    /// by spdx id, or key if there is none
+    pub by_license: Vec<Count>,
+    /// repos can have several topics or none, so these don't add up to the repo count
+    pub by_topic: Vec<Count>,
+    /// by time since creation, youngest first, repos without creation date count as `unknown`
+    pub by_age: Vec<Count>,
+    pub archived: usize,
+    /// archived repos divided by all repos
+    pub archived_share: f64,
+    /// the repos pushed to last, newest first
+    pub recently_pushed: Vec<Pushed>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Count {
+    pub name: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Pushed {
+    pub full_name: String,
+    pub url: String,
+    pub pushed_at: DateTime<Utc>,
+    pub star_count: u64,
+}
+
+impl Stats {
+    /// compute the stats of repos, with ages as of now
+    pub fn new(repos: &[Repo], now: DateTime<Utc>) -> Self {
+        let archived = repos.iter().filter(|repo| repo.archived).count();
+
+        let mut recently_pushed: Vec<Pushed> = repos
+            .iter()
+            .filter_map(|repo| {
+                Some(Pushed {
+                    full_name: repo.full_name.clone(),
+                    url: repo.url.clone(),
+                    pushed_at: repo.pushed_at?,
+                    star_count: repo.star_count,
+                })
+            })
+            .collect();
+        recently_pushed.sort_by_key(|repo| Reverse(repo.pushed_at));
+        recently_pushed.truncate(RECENTLY_PUSHED);
+
+        Stats {
+            repo_count: repos.len(),
+            star_total: repos.iter().map(|repo| repo.star_count).sum(),
+            star_median: median(repos.iter().map(|repo| repo.star_count).collect()),
+            by_language: count(repos.iter().map(|repo| repo.language.as_deref().unwrap_or(NONE))),
+            by_owner: count(repos.iter().map(|repo| repo.owner.login.as_str())),
+            by_license: count(repos.iter().map(|repo| repo.license_id().unwrap_or(NONE))),
+            by_topic: count(repos.iter().flat_map(|repo| repo.topics.iter().map(String::as_str))),
+            by_age: ages(repos, now),
+            archived,
+            archived_share: share(archived, repos.len()),
+            recently_pushed,
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(self).map_err(|err| Error::parse("Could not serialize stats as json", err))
+    }
+}
+
+/// how often each name occurs, most common first and alphabetically on ties
+fn count<'a>(names: impl Iterator<Item = &'a str>) -> Vec<Count> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for name in names {
+        *counts.entry(name).or_default() += 1;
+    }
+    let mut counts: Vec<Count> = counts
+        .into_iter()
+        .map(|(name, count)| Count {
+            name: name.to_string(),
+            count,
+        })
+        .collect();
+    counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    counts
+}
+
+/// every age bucket in order, then `unknown` if some repos have no creation date
+fn ages(repos: &[Repo], now: DateTime<Utc>) -> Vec<Count> {
+    let mut counts = vec![0; AGE_BUCKETS.len()];
+    let mut unknown = 0;
+    for repo in repos {
+        match repo.created_at {
+            Some(created_at) => {
+                // a year of 365.25 days, close enough for buckets this wide
+                let years = (now - created_at).num_days() as f64 / 365.25;
+                let bucket = AGE_BUCKETS
+                    .iter()
+                    .position(|&(below, _)| years < below as f64)
+                    .unwrap_or(AGE_BUCKETS.len() - 1);
+                counts[bucket] += 1;
+            }
+            None => unknown += 1,
+        }
+    }
+
+    let mut ages: Vec<Count> = AGE_BUCKETS
+        .iter()
+        .zip(counts)
+        .map(|(&(_, name), count)| Count {
+            name: name.to_string(),
+            count,
+        })
+        .collect();
+    if unknown > 0 {
+        ages.push(Count {
+            name: "unknown".to_string(),
+            count: unknown,
+        });
+    }
+    ages
+}
+
+/// 0 for no values
+fn median(mut values: Vec<u64>) -> f64 {
+    values.sort_unstable();
+    let middle = values.len() / 2;
+    match values.len() {
+        0 => 0.0,
+        len if len % 2 == 0 => (values[middle - 1] as f64 + values[middle] as f64) / 2.0,
+        _ => values[middle] as f64,
+    }
+}
+
+/// 0 for an empty list, which has no share of anything
+pub fn share(count: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        count as f64 / total as f64
+    }
+}
@@ -0,0 +1,173 @@
+mod common;
+
+use common::{paginated, StandIn};
+
+use github_most_popular::auth::parse_gh_hosts;
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// run against the stand-in with every token source isolated to dir
+fn run(stand_in: &StandIn, dir: &Path) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_github-most-popular"));
+    command
+        .current_dir(dir)
+        .env("GITHUB_API_URL", stand_in.url())
+        .env("STARRED_REPOS_CACHE_DIR", dir.join("cache"))
+        .env("STARRED_REPOS_DATA_DIR", dir.join("data"))
+        .env("HOME", dir)
+        .env("XDG_CONFIG_HOME", dir.join("config"))
+        .env("GH_CONFIG_DIR", dir.join("gh"))
+        .env_remove("GITHUB_TOKEN")
+        .env_remove("GH_TOKEN")
+        .env_remove("GITHUB_ACCESS")
+        .args(["-u", "octocat", "-j", "out.json"]);
+    command
+}
+
+fn authorization(stand_in: &StandIn) -> Option<String> {
+    stand_in.requests()[0]
+        .header("Authorization")
+        .map(str::to_string)
+}
+
+fn write(path: &Path, contents: &str) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, contents).unwrap();
+}
+
+#[test]
+fn requests_are_anonymous_without_a_token() {
+    let stand_in = StandIn::start(|request, base| paginated(request, base, 3));
+    let dir = tempfile::tempdir().unwrap();
+
+    let status = run(&stand_in, dir.path()).status().unwrap();
+
+    assert!(status.success());
+    assert_eq!(authorization(&stand_in), None);
+    assert!(dir.path().join("out.json").exists());
+}
+
+#[test]
+fn sources_are_tried_in_order() {
+    let dir = tempfile::tempdir().unwrap();
+    write(
+        &dir.path().join("config/starred-repos/config.toml"),
+        "token = \"from-config\"\n",
+    );
+    write(
+        &dir.path().join("gh/hosts.yml"),
+        "github.com:\n    user: octocat\n    oauth_token: from-gh\n",
+    );
+    write(&dir.path().join("token"), "from-file\n");
+
+    let expect = |configure: &dyn Fn(&mut Command), token: &str| {
+        let stand_in = StandIn::start(|request, base| paginated(request, base, 3));
+        let mut command = run(&stand_in, dir.path());
+        configure(&mut command);
+        assert!(command.status().unwrap().success());
+        assert_eq!(authorization(&stand_in), Some(format!("Bearer {}", token)));
+    };
+
+    expect(&|_| {}, "from-gh");
+    expect(
+        &|c| {
+            c.env("GITHUB_ACCESS", "from-access");
+        },
+        "from-access",
+    );
+    expect(
+        &|c| {
+            c.env("GITHUB_ACCESS", "from-access")
+                .env("GH_TOKEN", "from-gh-token");
+        },
+        "from-gh-token",
+    );
+    expect(
+        &|c| {
+            c.env("GH_TOKEN", "from-gh-token")
+                .env("GITHUB_TOKEN", "from-github-token");
+        },
+        "from-github-token",
+    );
+    expect(
+        &|c| {
+            c.env("GITHUB_TOKEN", "from-github-token")
+                .args(["--token-file", "token"]);
+        },
+        "from-file",
+    );
+
+    fs::remove_file(dir.path().join("gh/hosts.yml")).unwrap();
+    expect(&|_| {}, "from-config");
+}
+
+#[test]
+fn anonymous_ignores_configured_tokens() {
+    let stand_in = StandIn::start(|request, base| paginated(request, base, 3));
+    let dir = tempfile::tempdir().unwrap();
+
+    let status = run(&stand_in, dir.path())
+        .env("GITHUB_TOKEN", "from-github-token")
+        .arg("--anonymous")
+        .status()
+        .unwrap();
+
+    assert!(status.success());
+    assert_eq!(authorization(&stand_in), None);
+}
+
+#[test]
+fn missing_token_file_is_reported() {
+    let stand_in = StandIn::start(|request, base| paginated(request, base, 3));
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = run(&stand_in, dir.path())
+        .env("GITHUB_TOKEN", "from-github-token")
+        .args(["--token-file", "missing"])
+        .output()
+        .unwrap();
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Could not read token file missing"),
+        "{}",
+        stderr
+    );
+    assert_eq!(output.status.code(), Some(8));
+    assert!(stand_in.requests().is_empty());
+}
+
+#[test]
+fn gh_hosts_prefers_the_active_account() {
+    let hosts = "\
+github.example.com:
+    oauth_token: enterprise
+github.com:
+    users:
+        octocat:
+            oauth_token: \"listed\"
+    git_protocol: https
+    user: octocat
+    oauth_token: \"active\"
+";
+
+    assert_eq!(
+        parse_gh_hosts(hosts, "github.com").as_deref(),
+        Some("active")
+    );
+    assert_eq!(
+        parse_gh_hosts(hosts, "github.example.com").as_deref(),
+        Some("enterprise")
+    );
+    assert_eq!(parse_gh_hosts(hosts, "gitlab.com"), None);
+}
+
+#[test]
+fn gh_hosts_without_token_in_file() {
+    let hosts =
+        "github.com:\n    users:\n        octocat:\n    git_protocol: ssh\n    user: octocat\n";
+
+    assert_eq!(parse_gh_hosts(hosts, "github.com"), None);
+}
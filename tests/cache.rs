@@ -0,0 +1,385 @@
+mod common;
+
+use common::{repo_json, Response, StandIn};
+
+use github_most_popular::{
+    Cache, CacheEntry, CacheMode, CachedPage, FileCache, Repo, StarredClient, CACHE_VERSION,
+};
+
+use chrono::{Duration as ChronoDuration, Utc};
+
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+const ETAG: &str = "W/\"starred-v1\"";
+const LAST_MODIFIED: &str = "Tue, 25 Oct 2022 06:52:35 GMT";
+
+/// serves two repos, answering 304 when the client already has them
+fn stand_in() -> StandIn {
+    StandIn::start(|request, _| {
+        if request.header("If-None-Match") == Some(ETAG) {
+            return Response::status(304);
+        }
+        let body = serde_json::json!([repo_json(1), repo_json(2)]);
+        Response::json(body.to_string())
+            .header("ETag", ETAG)
+            .header("Last-Modified", LAST_MODIFIED)
+    })
+}
+
+fn client(stand_in: &StandIn, dir: &Path) -> StarredClient {
+    StarredClient::new()
+        .with_api_url(stand_in.url())
+        .with_token("test-token")
+        .with_cache(FileCache::new(dir))
+}
+
+/// put an entry fetched `age` ago
+fn seed(stand_in: &StandIn, client: &StarredClient, dir: &Path, age: ChronoDuration, etag: &str) {
+    seed_user(stand_in, client, dir, "octocat", age, etag);
+}
+
+fn seed_user(stand_in: &StandIn, client: &StarredClient, dir: &Path, user: &str, age: ChronoDuration, etag: &str) {
+    let entry = CacheEntry {
+        version: CACHE_VERSION,
+        user: user.to_string(),
+        fetched_at: Utc::now() - age,
+        pages: vec![CachedPage {
+            url: format!("{}/users/{}/starred?per_page=100", stand_in.url(), user),
+            etag: Some(etag.to_string()),
+            last_modified: None,
+            count: 1,
+        }],
+        repos: vec![serde_json::from_value(repo_json(7)).unwrap()],
+    };
+    let key = client.cache_key(user).unwrap();
+    FileCache::new(dir).put(&key, &entry).unwrap();
+}
+
+#[test]
+fn records_fetch_time_and_validators() {
+    let stand_in = stand_in();
+    let dir = tempfile::tempdir().unwrap();
+
+    let client = client(&stand_in, dir.path());
+    let fetched = client.fetch_starred("octocat", None).unwrap();
+
+    assert!(fetched.from_network);
+    let entry = FileCache::new(dir.path()).get(&client.cache_key("octocat").unwrap()).unwrap();
+    assert_eq!(entry.pages.len(), 1);
+    assert_eq!(entry.pages[0].etag.as_deref(), Some(ETAG));
+    assert_eq!(entry.pages[0].last_modified.as_deref(), Some(LAST_MODIFIED));
+    assert_eq!(entry.pages[0].count, 2);
+    assert_eq!(entry.fetched_at, fetched.fetched_at);
+    assert_eq!(entry.repos.len(), 2);
+}
+
+#[test]
+fn fresh_entry_skips_network() {
+    let stand_in = stand_in();
+    let dir = tempfile::tempdir().unwrap();
+    let client = client(&stand_in, dir.path()).with_max_age(Duration::from_secs(60 * 60));
+    seed(&stand_in, &client, dir.path(), ChronoDuration::minutes(5), "W/\"other\"");
+
+    let fetched = client.fetch_starred("octocat", None).unwrap();
+
+    assert!(!fetched.from_network);
+    assert_eq!(fetched.repos[0].name, "repo-7");
+    assert!(stand_in.requests().is_empty());
+}
+
+#[test]
+fn stale_entry_is_revalidated_and_reused_on_304() {
+    let stand_in = stand_in();
+    let dir = tempfile::tempdir().unwrap();
+    let client = client(&stand_in, dir.path());
+    seed(&stand_in, &client, dir.path(), ChronoDuration::hours(2), ETAG);
+
+    let fetched = client.fetch_starred("octocat", None).unwrap();
+
+    let requests = stand_in.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].header("If-None-Match"), Some(ETAG));
+    assert_eq!(fetched.repos.len(), 1);
+    assert_eq!(fetched.repos[0].name, "repo-7");
+    // revalidating counts as fetching, the entry is fresh again
+    let entry = FileCache::new(dir.path()).get(&client.cache_key("octocat").unwrap()).unwrap();
+    assert!(entry.is_fresh(Duration::from_secs(60)));
+}
+
+/// serves 150 repos on two pages, each with an etag of its own that it answers 304 to
+fn two_pages(unstarred: &Arc<AtomicBool>) -> StandIn {
+    let unstarred = Arc::clone(unstarred);
+    StandIn::start(move |request, base| {
+        // repo-120 sits on page 2, unstarring it leaves page 1 as it was
+        let repos: Vec<_> = (0..150)
+            .filter(|&i| !(i == 120 && unstarred.load(Ordering::SeqCst)))
+            .map(repo_json)
+            .collect();
+        let page: usize = request.query("page").unwrap_or("1").parse().unwrap();
+        let items: Vec<_> = repos.into_iter().skip((page - 1) * 100).take(100).collect();
+        let etag = format!("W/\"page-{}-{}\"", page, items.len());
+        if request.header("If-None-Match") == Some(etag.as_str()) {
+            return Response::status(304);
+        }
+        let mut response = Response::json(serde_json::to_string(&items).unwrap()).header("ETag", etag);
+        if page == 1 {
+            response = response.header("Link", format!("<{}/users/octocat/starred?page=2>; rel=\"next\"", base));
+        }
+        response
+    })
+}
+
+#[test]
+fn unchanged_multi_page_list_is_revalidated_page_by_page() {
+    let stand_in = two_pages(&Arc::new(AtomicBool::new(false)));
+    let dir = tempfile::tempdir().unwrap();
+    let client = client(&stand_in, dir.path()).with_max_age(Duration::ZERO);
+    assert_eq!(client.starred("octocat", None).unwrap().len(), 150);
+
+    let fetched = client.fetch_starred("octocat", None).unwrap();
+
+    assert_eq!(fetched.repos.len(), 150);
+    let requests = stand_in.requests();
+    assert_eq!(requests.len(), 4);
+    assert_eq!(requests[2].header("If-None-Match"), Some("W/\"page-1-100\""));
+    assert_eq!(requests[3].header("If-None-Match"), Some("W/\"page-2-50\""));
+    assert!(requests[3].path.contains("page=2"));
+}
+
+#[test]
+fn changed_second_page_is_fetched_again() {
+    let unstarred = Arc::new(AtomicBool::new(false));
+    let stand_in = two_pages(&unstarred);
+    let dir = tempfile::tempdir().unwrap();
+    let client = client(&stand_in, dir.path()).with_max_age(Duration::ZERO);
+    assert_eq!(client.starred("octocat", None).unwrap().len(), 150);
+
+    unstarred.store(true, Ordering::SeqCst);
+    let repos = client.starred("octocat", None).unwrap();
+
+    assert_eq!(repos.len(), 149);
+    assert!(repos.iter().all(|repo| repo.name != "repo-120"));
+    let requests = stand_in.requests();
+    assert_eq!(requests.len(), 4);
+    assert_eq!(requests[2].header("If-None-Match"), Some("W/\"page-1-100\""));
+    assert_eq!(requests[3].header("If-None-Match"), Some("W/\"page-2-50\""));
+    let entry = FileCache::new(dir.path()).get(&client.cache_key("octocat").unwrap()).unwrap();
+    let etags: Vec<_> = entry.pages.iter().map(|page| page.etag.as_deref().unwrap()).collect();
+    assert_eq!(etags, ["W/\"page-1-100\"", "W/\"page-2-49\""]);
+    assert_eq!(entry.repos.len(), 149);
+}
+
+#[test]
+fn changed_list_replaces_stale_entry() {
+    let stand_in = stand_in();
+    let dir = tempfile::tempdir().unwrap();
+    let client = client(&stand_in, dir.path());
+    seed(&stand_in, &client, dir.path(), ChronoDuration::hours(2), "W/\"starred-v0\"");
+
+    let repos = client.starred("octocat", None).unwrap();
+
+    assert_eq!(repos.len(), 2);
+    let entry = FileCache::new(dir.path()).get(&client.cache_key("octocat").unwrap()).unwrap();
+    assert_eq!(entry.pages[0].etag.as_deref(), Some(ETAG));
+}
+
+#[test]
+fn lists_entries_with_user_and_count() {
+    let stand_in = stand_in();
+    let dir = tempfile::tempdir().unwrap();
+    let client = client(&stand_in, dir.path());
+    seed_user(&stand_in, &client, dir.path(), "tecosaur", ChronoDuration::hours(3), ETAG);
+    client.starred("octocat", None).unwrap();
+
+    let entries = FileCache::new(dir.path()).entries().unwrap();
+
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].user, "octocat");
+    assert_eq!(entries[0].count, 2);
+    assert!(entries[0].size > 0);
+    assert_eq!(entries[1].user, "tecosaur");
+    assert!(entries[1].age() >= Duration::from_secs(3 * 60 * 60));
+}
+
+#[test]
+fn clears_single_user() {
+    let stand_in = stand_in();
+    let dir = tempfile::tempdir().unwrap();
+    let client = client(&stand_in, dir.path());
+    seed_user(&stand_in, &client, dir.path(), "tecosaur", ChronoDuration::zero(), ETAG);
+    seed_user(&stand_in, &client, dir.path(), "hlissner", ChronoDuration::zero(), ETAG);
+    let cache = FileCache::new(dir.path());
+
+    assert_eq!(cache.clear_user("TECOSAUR").unwrap(), 1);
+
+    let users: Vec<_> = cache.entries().unwrap().into_iter().map(|e| e.user).collect();
+    assert_eq!(users, ["hlissner"]);
+}
+
+#[test]
+fn clear_only_removes_cache_entries() {
+    let stand_in = stand_in();
+    let dir = tempfile::tempdir().unwrap();
+    let shared = dir.path().join("shared");
+    let client = client(&stand_in, &shared);
+    seed_user(&stand_in, &client, &shared, "tecosaur", ChronoDuration::zero(), ETAG);
+    seed_user(&stand_in, &client, &shared, "hlissner", ChronoDuration::zero(), ETAG);
+    fs::write(shared.join("notes.txt"), "keep me").unwrap();
+    fs::write(shared.join("starred-corrupt"), "not json").unwrap();
+    let cache = FileCache::new(&shared);
+
+    assert_eq!(cache.clear().unwrap(), 3);
+
+    assert!(cache.entries().unwrap().is_empty());
+    assert_eq!(fs::read_to_string(shared.join("notes.txt")).unwrap(), "keep me");
+    fs::remove_file(shared.join("notes.txt")).unwrap();
+    seed_user(&stand_in, &client, &shared, "tecosaur", ChronoDuration::zero(), ETAG);
+    assert_eq!(cache.clear().unwrap(), 1);
+    assert!(!shared.exists());
+    assert_eq!(cache.clear().unwrap(), 0);
+}
+
+#[test]
+fn prunes_old_entries() {
+    let stand_in = stand_in();
+    let dir = tempfile::tempdir().unwrap();
+    let client = client(&stand_in, dir.path());
+    seed_user(&stand_in, &client, dir.path(), "tecosaur", ChronoDuration::days(10), ETAG);
+    seed_user(&stand_in, &client, dir.path(), "hlissner", ChronoDuration::days(2), ETAG);
+    let cache = FileCache::new(dir.path());
+
+    assert_eq!(cache.prune(Duration::from_secs(7 * 24 * 60 * 60)).unwrap(), 1);
+
+    let users: Vec<_> = cache.entries().unwrap().into_iter().map(|e| e.user).collect();
+    assert_eq!(users, ["hlissner"]);
+}
+
+#[test]
+fn stores_parsed_repos_compactly() {
+    let raw = include_str!("fixtures/starred.json");
+    let stand_in = StandIn::start(move |_, _| Response::json(raw));
+    let dir = tempfile::tempdir().unwrap();
+    let client = client(&stand_in, dir.path());
+
+    let repos = client.starred("octocat", None).unwrap();
+
+    let key = client.cache_key("octocat").unwrap();
+    let stored = std::fs::read_to_string(dir.path().join(key.as_str())).unwrap();
+    let stored: serde_json::Value = serde_json::from_str(&stored).unwrap();
+    assert_eq!(stored["version"], CACHE_VERSION);
+    let cached: Vec<Repo> = serde_json::from_value(stored["repos"].clone()).unwrap();
+    assert_eq!(cached, repos);
+    assert!(stored.to_string().len() < raw.len() / 2);
+}
+
+#[test]
+fn migrates_unversioned_entries() {
+    let stand_in = stand_in();
+    let dir = tempfile::tempdir().unwrap();
+    let client = client(&stand_in, dir.path());
+    let key = client.cache_key("octocat").unwrap();
+    let unversioned = serde_json::json!({
+        "user": "octocat",
+        "fetched_at": Utc::now(),
+        "etag": ETAG,
+        "last_modified": null,
+        "items": [{ "starred_at": "2022-10-20T08:00:00Z", "repo": repo_json(3) }],
+    });
+    std::fs::write(dir.path().join(key.as_str()), unversioned.to_string()).unwrap();
+
+    let entry = FileCache::new(dir.path()).get(&key).unwrap();
+
+    assert_eq!(entry.version, CACHE_VERSION);
+    // the validators of other pages are unknown, so it is fetched again in full
+    assert!(entry.pages.is_empty());
+    assert_eq!(entry.repos[0].name, "repo-3");
+    assert!(entry.repos[0].starred_at.is_some());
+}
+
+#[test]
+fn keeps_entries_of_newer_versions() {
+    let stand_in = stand_in();
+    let dir = tempfile::tempdir().unwrap();
+    let client = client(&stand_in, dir.path());
+    let key = client.cache_key("octocat").unwrap();
+    let path = dir.path().join(key.as_str());
+    let future = serde_json::json!({ "version": CACHE_VERSION + 1, "repos": "something new" });
+    std::fs::write(&path, future.to_string()).unwrap();
+
+    let cache = FileCache::new(dir.path());
+    assert!(cache.entries().unwrap().is_empty());
+    assert!(cache.get(&key).is_none());
+    assert!(path.exists());
+
+    // the next fetch starts over from github
+    assert_eq!(client.starred("octocat", None).unwrap().len(), 2);
+    assert_eq!(stand_in.requests().len(), 1);
+    assert!(stand_in.requests()[0].header("If-None-Match").is_none());
+}
+
+#[test]
+fn removes_corrupt_entries_when_fetching_but_not_listing() {
+    let stand_in = stand_in();
+    let dir = tempfile::tempdir().unwrap();
+    let client = client(&stand_in, dir.path());
+    let key = client.cache_key("octocat").unwrap();
+    let path = dir.path().join(key.as_str());
+    std::fs::write(&path, "{ not json").unwrap();
+
+    let cache = FileCache::new(dir.path());
+    assert!(cache.entries().unwrap().is_empty());
+    assert!(path.exists());
+    assert!(cache.get(&key).is_none());
+    assert!(!path.exists());
+}
+
+#[test]
+fn offline_serves_stale_entries_without_network() {
+    let stand_in = stand_in();
+    let dir = tempfile::tempdir().unwrap();
+    let client = client(&stand_in, dir.path()).with_cache_mode(CacheMode::Offline);
+    seed(&stand_in, &client, dir.path(), ChronoDuration::days(30), ETAG);
+
+    let fetched = client.fetch_starred("octocat", None).unwrap();
+
+    assert!(!fetched.from_network);
+    assert_eq!(fetched.repos[0].name, "repo-7");
+    assert!(stand_in.requests().is_empty());
+}
+
+#[test]
+fn offline_miss_fails_without_token_or_network() {
+    let stand_in = stand_in();
+    let dir = tempfile::tempdir().unwrap();
+    let client = StarredClient::new()
+        .with_api_url(stand_in.url())
+        .with_cache(FileCache::new(dir.path()))
+        .with_cache_mode(CacheMode::Offline);
+
+    let err = client.starred("octocat", None).unwrap_err().to_string();
+
+    assert_eq!(err, "No cached starred list for octocat, run without --offline to fetch it");
+    assert!(stand_in.requests().is_empty());
+}
+
+#[test]
+fn refresh_refetches_fresh_entries_and_updates_cache() {
+    let stand_in = stand_in();
+    let dir = tempfile::tempdir().unwrap();
+    let client = client(&stand_in, dir.path()).with_cache_mode(CacheMode::Refresh);
+    seed(&stand_in, &client, dir.path(), ChronoDuration::minutes(1), ETAG);
+
+    let fetched = client.fetch_starred("octocat", None).unwrap();
+
+    assert!(fetched.from_network);
+    assert_eq!(fetched.repos.len(), 2);
+    let requests = stand_in.requests();
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0].header("If-None-Match").is_none());
+    let entry = FileCache::new(dir.path()).get(&client.cache_key("octocat").unwrap()).unwrap();
+    assert_eq!(entry.repos.len(), 2);
+}
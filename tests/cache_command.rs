@@ -0,0 +1,80 @@
+mod common;
+
+use common::{paginated, run, StandIn};
+
+use std::fs;
+use std::process::Output;
+
+fn stdout(output: Output) -> String {
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn list_clear_and_stats() {
+    let stand_in = StandIn::start(|request, base| paginated(request, base, 3));
+    let dir = tempfile::tempdir().unwrap();
+    run(&stand_in, dir.path(), &["-u", "octocat", "-j", "out.json"]);
+    run(&stand_in, dir.path(), &["-u", "hlissner", "-j", "out.json"]);
+
+    let list = stdout(run(&stand_in, dir.path(), &["cache", "list"]));
+    let lines: Vec<_> = list.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("USER"));
+    assert!(lines[1].starts_with("hlissner"));
+    assert!(lines[2].starts_with("octocat") && lines[2].ends_with(" 3"));
+
+    let stats = stdout(run(&stand_in, dir.path(), &["cache", "stats"]));
+    assert!(stats.contains("Lists:       2 (2 users)"));
+    assert!(stats.contains("Repos:       6"));
+
+    let clear = stdout(run(&stand_in, dir.path(), &["cache", "clear", "--user", "octocat"]));
+    assert_eq!(clear, "Removed 1 cached lists of octocat\n");
+    let list = stdout(run(&stand_in, dir.path(), &["cache", "list"]));
+    assert_eq!(list.lines().count(), 2);
+
+    let prune = stdout(run(&stand_in, dir.path(), &["cache", "prune", "--older-than", "1d"]));
+    assert_eq!(prune, "Removed 0 cached lists\n");
+
+    let clear = stdout(run(&stand_in, dir.path(), &["cache", "clear"]));
+    assert_eq!(clear, "Removed 1 cached lists\n");
+    assert!(!dir.path().join("cache").exists());
+}
+
+#[test]
+fn clear_keeps_other_files_in_the_cache_dir() {
+    let stand_in = StandIn::start(|request, base| paginated(request, base, 3));
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("notes.txt"), "keep me").unwrap();
+    run(&stand_in, dir.path(), &["-u", "octocat", "--cache-dir", ".", "-j", "out.json"]);
+
+    let clear = stdout(run(&stand_in, dir.path(), &["cache", "clear", "--cache-dir", "."]));
+
+    assert_eq!(clear, "Removed 1 cached lists\n");
+    assert!(dir.path().join("notes.txt").exists());
+    assert!(dir.path().join("out.json").exists());
+}
+
+#[test]
+fn cache_dir_flag_applies_to_subcommands() {
+    let stand_in = StandIn::start(|request, base| paginated(request, base, 3));
+    let dir = tempfile::tempdir().unwrap();
+    run(&stand_in, dir.path(), &["-u", "octocat", "--cache-dir", "elsewhere", "-j", "out.json"]);
+
+    let list = stdout(run(&stand_in, dir.path(), &["cache", "list", "--cache-dir", "elsewhere"]));
+    assert_eq!(list.lines().count(), 2);
+    let list = stdout(run(&stand_in, dir.path(), &["--cache-dir", "elsewhere", "cache", "list"]));
+    assert_eq!(list.lines().count(), 2);
+}
+
+#[test]
+fn clear_flag_without_a_user_only_clears() {
+    let stand_in = StandIn::start(|request, base| paginated(request, base, 3));
+    let dir = tempfile::tempdir().unwrap();
+    run(&stand_in, dir.path(), &["-u", "octocat", "-j", "out.json"]);
+
+    let output = run(&stand_in, dir.path(), &["-c"]);
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(!dir.path().join("cache").exists());
+    assert_eq!(stand_in.requests().len(), 1);
+}
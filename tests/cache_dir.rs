@@ -0,0 +1,70 @@
+mod common;
+
+use common::{cached_file, paginated, StandIn};
+
+use std::path::Path;
+use std::process::Command;
+
+fn run(stand_in: &StandIn, dir: &Path) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_github-most-popular"));
+    command
+        .current_dir(dir)
+        .env("GITHUB_API_URL", stand_in.url())
+        .env("GITHUB_ACCESS", "test-token")
+        .env_remove("STARRED_REPOS_CACHE_DIR")
+        .env("STARRED_REPOS_DATA_DIR", dir.join("data"))
+        .args(["-u", "octocat", "-j", "out.json"]);
+    command
+}
+
+#[test]
+fn caches_under_xdg_cache_home() {
+    let stand_in = StandIn::start(|request, base| paginated(request, base, 3));
+    let dir = tempfile::tempdir().unwrap();
+
+    run(&stand_in, dir.path())
+        .env("XDG_CACHE_HOME", dir.path().join("xdg"))
+        .status()
+        .unwrap();
+
+    assert!(cached_file(&dir.path().join("xdg/starred-repos"), "octocat").is_some());
+    assert!(!dir.path().join("cache").exists());
+}
+
+#[test]
+fn cache_dir_flag_and_env_override_xdg() {
+    let stand_in = StandIn::start(|request, base| paginated(request, base, 3));
+    let dir = tempfile::tempdir().unwrap();
+
+    run(&stand_in, dir.path())
+        .env("XDG_CACHE_HOME", dir.path().join("xdg"))
+        .env("STARRED_REPOS_CACHE_DIR", dir.path().join("env"))
+        .status()
+        .unwrap();
+    run(&stand_in, dir.path())
+        .env("XDG_CACHE_HOME", dir.path().join("xdg"))
+        .args(["--cache-dir", "flag"])
+        .status()
+        .unwrap();
+
+    assert!(cached_file(&dir.path().join("env"), "octocat").is_some());
+    assert!(cached_file(&dir.path().join("flag"), "octocat").is_some());
+    assert!(!dir.path().join("xdg").exists());
+}
+
+#[test]
+fn unwritable_cache_dir_is_reported() {
+    let stand_in = StandIn::start(|request, base| paginated(request, base, 3));
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("not-a-dir"), "").unwrap();
+
+    let output = run(&stand_in, dir.path())
+        .args(["--cache-dir", "not-a-dir/cache"])
+        .output()
+        .unwrap();
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Could not create cache dir"), "{}", stderr);
+    assert_eq!(output.status.code(), Some(8));
+    assert!(!dir.path().join("out.json").exists());
+}
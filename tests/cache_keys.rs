@@ -0,0 +1,83 @@
+mod common;
+
+use common::{Response, StandIn};
+
+use github_most_popular::cache::validate_login;
+use github_most_popular::{CacheKey, FileCache, StarredClient};
+
+const PARAMS: &[(&str, &str)] = &[("per_page", "100"), ("accept", "application/vnd.github.star+json")];
+
+#[test]
+fn rejects_traversal_and_invalid_logins() {
+    for login in [
+        "../../etc/x",
+        "..",
+        "a/b",
+        "a\\b",
+        "/etc",
+        "",
+        "-leading",
+        "octo cat",
+        "octo\0cat",
+        "octo.cat",
+        "ünïcode",
+        &"x".repeat(40),
+    ] {
+        assert!(validate_login(login).is_err(), "{:?} accepted", login);
+        assert!(CacheKey::starred(login, PARAMS).is_err(), "{:?} keyed", login);
+    }
+}
+
+#[test]
+fn accepts_github_logins() {
+    for login in ["hlissner", "tecosaur", "eccentric-j", "abo-abo", "a", &"x".repeat(39)] {
+        validate_login(login).unwrap();
+    }
+}
+
+#[test]
+fn keys_are_plain_file_names() {
+    let key = CacheKey::starred("Eccentric-J", PARAMS).unwrap();
+
+    assert!(key.as_str().starts_with("starred-eccentric-j-"));
+    assert!(key
+        .as_str()
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-'));
+    assert_eq!(key, CacheKey::starred("eccentric-j", PARAMS).unwrap());
+}
+
+#[test]
+fn keys_depend_on_query_params() {
+    let star_json = CacheKey::starred("octocat", PARAMS).unwrap();
+    let plain_json = CacheKey::starred(
+        "octocat",
+        &[("per_page", "100"), ("accept", "application/vnd.github+json")],
+    )
+    .unwrap();
+    let small_pages = CacheKey::starred(
+        "octocat",
+        &[("per_page", "10"), ("accept", "application/vnd.github.star+json")],
+    )
+    .unwrap();
+
+    assert_ne!(star_json, plain_json);
+    assert_ne!(star_json, small_pages);
+}
+
+#[test]
+fn client_rejects_traversal_before_any_request_or_write() {
+    let stand_in = StandIn::start(|_, _| Response::json("[]"));
+    let dir = tempfile::tempdir().unwrap();
+    let client = StarredClient::new()
+        .with_api_url(stand_in.url())
+        .with_token("test-token")
+        .with_cache(FileCache::new(dir.path().join("cache")));
+
+    let err = client.starred("../../etc/x", None).unwrap_err();
+
+    assert!(err.to_string().contains("not a valid GitHub login"));
+    assert!(stand_in.requests().is_empty());
+    assert!(!dir.path().join("cache").exists());
+    assert!(!dir.path().join("etc").exists());
+}
@@ -0,0 +1,210 @@
+//! Minimal HTTP stand-in for the GitHub API, serving canned responses on a local port.
+
+#![allow(dead_code)]
+
+use github_most_popular::Repo;
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// a request as seen by the stand-in
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+}
+
+impl Request {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// value of a query parameter in the request path
+    pub fn query(&self, name: &str) -> Option<&str> {
+        let (_, query) = self.path.split_once('?')?;
+        query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value)
+    }
+}
+
+/// a canned response
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Response {
+    pub fn json(body: impl Into<String>) -> Self {
+        Response {
+            status: 200,
+            headers: vec![("Content-Type".into(), "application/json".into())],
+            body: body.into(),
+        }
+    }
+
+    pub fn status(status: u16) -> Self {
+        Response {
+            status,
+            headers: Vec::new(),
+            body: String::new(),
+        }
+    }
+
+    pub fn header(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+}
+
+pub struct StandIn {
+    url: String,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl StandIn {
+    /// start serving `handler` on a free local port
+    pub fn start<F>(handler: F) -> Self
+    where
+        F: Fn(&Request, &str) -> Response + Send + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind stand-in");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let base = url.clone();
+        let seen = Arc::clone(&requests);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if let Some(request) = read_request(&stream) {
+                    let response = handler(&request, &base);
+                    seen.lock().unwrap().push(request);
+                    write_response(stream, response);
+                }
+            }
+        });
+
+        StandIn { url, requests }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+fn read_request(stream: &TcpStream) -> Option<Request> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    let path = line.split_whitespace().nth(1)?.to_string();
+
+    let mut headers = Vec::new();
+    loop {
+        line.clear();
+        reader.read_line(&mut line).ok()?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((key, value)) = header.split_once(':') {
+            headers.push((key.trim().to_string(), value.trim().to_string()));
+        }
+    }
+
+    Some(Request { path, headers })
+}
+
+fn write_response(mut stream: TcpStream, response: Response) {
+    let mut head = format!("HTTP/1.1 {} Stand-In\r\n", response.status);
+    for (key, value) in &response.headers {
+        head.push_str(&format!("{}: {}\r\n", key, value));
+    }
+    head.push_str(&format!(
+        "Content-Length: {}\r\nConnection: close\r\n\r\n",
+        response.body.len()
+    ));
+    let _ = stream.write_all(head.as_bytes());
+    let _ = stream.write_all(response.body.as_bytes());
+}
+
+/// run the binary in dir against the stand-in, with the cache and history kept in dir
+/// and `test-token` as the only token, whatever the environment has set
+pub fn run(stand_in: &StandIn, dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_github-most-popular"))
+        .current_dir(dir)
+        .env("GITHUB_API_URL", stand_in.url())
+        .env_remove("GITHUB_TOKEN")
+        .env_remove("GH_TOKEN")
+        .env("GITHUB_ACCESS", "test-token")
+        .env("STARRED_REPOS_CACHE_DIR", dir.join("cache"))
+        .env("STARRED_REPOS_DATA_DIR", dir.join("data"))
+        .env("NO_COLOR", "1")
+        .args(args)
+        .output()
+        .expect("run binary")
+}
+
+/// json for a starred repo numbered `i`
+pub fn repo_json(i: usize) -> serde_json::Value {
+    serde_json::json!({
+        "name": format!("repo-{}", i),
+        "html_url": format!("https://github.com/owner/repo-{}", i),
+        "description": format!("Repo number {}", i),
+        "stargazers_count": i,
+    })
+}
+
+/// the starred list in `fixtures/starred.json`, as github serves it
+pub const STARRED_JSON: &str = include_str!("../fixtures/starred.json");
+
+/// the repos of `STARRED_JSON`
+pub fn starred_fixture() -> Vec<Repo> {
+    serde_json::from_str(STARRED_JSON).expect("starred fixture parses")
+}
+
+/// serve `total` starred repos in pages of `per_page` query, linking pages like github does
+pub fn paginated(request: &Request, base: &str, total: usize) -> Response {
+    let per_page: usize = request.query("per_page").unwrap_or("30").parse().unwrap();
+    let page: usize = request.query("page").unwrap_or("1").parse().unwrap();
+    let path = request.path.split('?').next().unwrap();
+
+    let start = (page - 1) * per_page;
+    let items: Vec<_> = (start..total.min(start + per_page)).map(repo_json).collect();
+
+    let mut response = Response::json(serde_json::to_string(&items).unwrap());
+    let last = total.div_ceil(per_page).max(1);
+    if page < last {
+        response = response.header(
+            "Link",
+            format!(
+                "<{base}{path}?per_page={per_page}&page={next}>; rel=\"next\", <{base}{path}?per_page={per_page}&page={last}>; rel=\"last\"",
+                next = page + 1
+            ),
+        );
+    }
+    response
+}
+
+/// the cache file holding the starred list of user in dir, if any
+pub fn cached_file(dir: &Path, user: &str) -> Option<PathBuf> {
+    let prefix = format!("starred-{}-", user);
+    std::fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|path| path.file_name().unwrap().to_string_lossy().starts_with(&prefix))
+}
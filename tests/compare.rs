@@ -0,0 +1,108 @@
+mod common;
+
+use common::{repo_json, run, Response, StandIn};
+
+use github_most_popular::{Comparison, Repo};
+
+fn repos(numbers: &[usize]) -> Vec<Repo> {
+    numbers
+        .iter()
+        .map(|&i| serde_json::from_value(repo_json(i)).unwrap())
+        .collect()
+}
+
+fn names(repos: &[Repo]) -> Vec<&str> {
+    repos.iter().map(|repo| repo.name.as_str()).collect()
+}
+
+#[test]
+fn finds_common_and_unique_repos() {
+    let comparison = Comparison::new(vec![
+        ("alice".into(), repos(&[1, 2, 3, 4])),
+        ("bob".into(), repos(&[4, 3, 5])),
+        ("carol".into(), repos(&[3, 4, 5, 6])),
+    ]);
+
+    assert_eq!(names(&comparison.common), ["repo-3", "repo-4"]);
+    assert_eq!(names(&comparison.unique[0].repos), ["repo-1", "repo-2"]);
+    assert!(comparison.unique[1].repos.is_empty());
+    assert_eq!(names(&comparison.unique[2].repos), ["repo-6"]);
+    assert_eq!(comparison.total, 6);
+    assert!((comparison.similarity - 2.0 / 6.0).abs() < 1e-9);
+
+    let pairs: Vec<_> = comparison
+        .pairs
+        .iter()
+        .map(|pair| (pair.users.0.as_str(), pair.users.1.as_str(), pair.common))
+        .collect();
+    assert_eq!(pairs, [("alice", "bob", 2), ("alice", "carol", 2), ("bob", "carol", 3)]);
+    assert!((comparison.pairs[2].similarity - 3.0 / 4.0).abs() < 1e-9);
+}
+
+#[test]
+fn disjoint_and_empty_lists_are_not_similar() {
+    let disjoint = Comparison::new(vec![("a".into(), repos(&[1])), ("b".into(), repos(&[2]))]);
+    let empty = Comparison::new(vec![("a".into(), Vec::new()), ("b".into(), Vec::new())]);
+
+    assert_eq!(disjoint.similarity, 0.0);
+    assert_eq!(empty.similarity, 0.0);
+    assert_eq!(empty.total, 0);
+}
+
+/// alice starred 1 to 3, bob 2 to 4
+fn stand_in() -> StandIn {
+    StandIn::start(|request, _| {
+        let numbers = if request.path.starts_with("/users/alice/") {
+            1..4
+        } else {
+            2..5
+        };
+        let items: Vec<_> = numbers.map(repo_json).collect();
+        Response::json(serde_json::to_string(&items).unwrap())
+    })
+}
+
+#[test]
+fn compare_command_prints_overlap() {
+    let stand_in = stand_in();
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = run(&stand_in, dir.path(), &["compare", "-u", "alice", "-u", "bob"]);
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("Similarity:  0.500 (2 of 4 repos in common)"), "{}", stdout);
+    assert!(stdout.contains("Starred by everyone (2)\n  https://github.com/owner/repo-2"), "{}", stdout);
+    assert!(stdout.contains("Only starred by alice (1)\n  https://github.com/owner/repo-1"), "{}", stdout);
+    assert!(stdout.contains("Only starred by bob (1)\n  https://github.com/owner/repo-4"), "{}", stdout);
+}
+
+#[test]
+fn compare_command_writes_json_and_reuses_cache() {
+    let stand_in = stand_in();
+    let dir = tempfile::tempdir().unwrap();
+
+    run(&stand_in, dir.path(), &["compare", "-u", "alice", "-u", "bob", "-j", "out.json"]);
+    let output = run(&stand_in, dir.path(), &["compare", "-u", "alice", "-u", "bob", "-j", "-"]);
+
+    let written: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(dir.path().join("out.json")).unwrap()).unwrap();
+    let printed: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(written, printed);
+    assert_eq!(printed["users"], serde_json::json!(["alice", "bob"]));
+    assert_eq!(printed["similarity"], 0.5);
+    assert_eq!(printed["common"].as_array().unwrap().len(), 2);
+    assert_eq!(printed["unique"][1]["user"], "bob");
+    assert_eq!(printed["pairs"][0]["common"], 2);
+    assert_eq!(stand_in.requests().len(), 2);
+}
+
+#[test]
+fn compare_needs_two_users() {
+    let stand_in = stand_in();
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = run(&stand_in, dir.path(), &["compare", "-u", "alice"]);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stand_in.requests().is_empty());
+}
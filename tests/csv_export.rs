@@ -0,0 +1,74 @@
+mod common;
+
+use common::starred_fixture;
+
+use github_most_popular::format::{self, Column};
+use github_most_popular::Repo;
+
+/// the starred fixture with a description that needs quoting
+fn fixture() -> Vec<Repo> {
+    let mut repos = starred_fixture();
+    repos[0].description = Some("An elisp package manager, \"fast\"\nand\tsimple".into());
+    repos
+}
+
+fn read(data: &str, delimiter: u8) -> Vec<csv::StringRecord> {
+    csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .from_reader(data.as_bytes())
+        .records()
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+#[test]
+fn writes_header_and_default_columns() {
+    let data = format::to_csv(&fixture(), Column::DEFAULT).unwrap();
+
+    assert!(data.starts_with("full_name,url,description,language,stars\n"));
+    let records = read(&data, b',');
+    assert_eq!(records.len(), 10);
+    assert_eq!(&records[1][0], "tecosaur/org-glossary");
+    assert_eq!(&records[1][4], "65");
+}
+
+#[test]
+fn quotes_delimiters_quotes_and_newlines() {
+    let repos = fixture();
+
+    for (data, delimiter) in [
+        (format::to_csv(&repos, Column::DEFAULT).unwrap(), b','),
+        (format::to_tsv(&repos, Column::DEFAULT).unwrap(), b'\t'),
+    ] {
+        let records = read(&data, delimiter);
+        assert_eq!(records.len(), 10);
+        assert_eq!(&records[0][2], repos[0].description.as_deref().unwrap());
+    }
+}
+
+#[test]
+fn selected_columns_map_onto_fields() {
+    let columns = Column::parse_list("name,owner,topics,license,archived,created").unwrap();
+    let data = format::to_csv(&fixture(), &columns).unwrap();
+    let records = read(&data, b',');
+
+    assert!(data.starts_with("name,owner,topics,license,archived,created\n"));
+    assert_eq!(
+        records[2].iter().collect::<Vec<_>>(),
+        [
+            "engrave-faces",
+            "tecosaur",
+            "emacs emacs-package",
+            "GPL-3.0",
+            "false",
+            "2021-01-26T08:26:46+00:00"
+        ]
+    );
+    // absent values become empty fields
+    assert_eq!(&records[3][2], "");
+}
+
+#[test]
+fn rejects_unknown_columns() {
+    assert!(Column::parse_list("name,popularity").is_err());
+}
@@ -0,0 +1,136 @@
+mod common;
+
+use common::{paginated, repo_json, run, StandIn};
+
+use github_most_popular::diff::Changed;
+use github_most_popular::{format, Diff, Repo, Snapshot};
+
+use chrono::Utc;
+
+use std::fs;
+
+fn repo(i: usize) -> Repo {
+    serde_json::from_value(repo_json(i)).unwrap()
+}
+
+/// repos 1 to 3 with repo 1 reworded, repo 2 at 40 stars, and repo 9 since unstarred
+fn snapshot() -> Vec<Repo> {
+    let mut reworded = repo(1);
+    reworded.description = Some("Old words".to_string());
+    let mut starred = repo(2);
+    starred.star_count = 40;
+    vec![reworded, starred, repo(3), repo(9)]
+}
+
+fn names(repos: &[Repo]) -> Vec<&str> {
+    repos.iter().map(|repo| repo.name.as_str()).collect()
+}
+
+#[test]
+fn finds_added_removed_and_changed_repos() {
+    let current: Vec<Repo> = (0..4).map(repo).collect();
+
+    let diff = Diff::new(&snapshot(), &current);
+
+    assert_eq!(names(&diff.added), ["repo-0"]);
+    assert_eq!(names(&diff.removed), ["repo-9"]);
+    assert_eq!(diff.changed.len(), 2);
+    assert_eq!(diff.changed[0].url, "https://github.com/owner/repo-1");
+    assert_eq!(diff.changed[0].stars, None);
+    assert_eq!(
+        diff.changed[0].description,
+        Some(Changed {
+            before: Some("Old words".to_string()),
+            after: Some("Repo number 1".to_string()),
+        })
+    );
+    assert_eq!(diff.changed[1].stars, Some(Changed { before: 40, after: 2 }));
+    assert_eq!(diff.changed[1].description, None);
+    assert!(Diff::new(&current, &current).is_empty());
+}
+
+#[test]
+fn loads_json_and_toml_snapshots() {
+    let dir = tempfile::tempdir().unwrap();
+    let json = dir.path().join("stars.json");
+    let toml = dir.path().join("stars.TOML");
+    fs::write(&json, format::to_json(&snapshot()).unwrap()).unwrap();
+    fs::write(&toml, format::to_toml("octocat", Utc::now(), &snapshot()).unwrap()).unwrap();
+
+    let from_json = Snapshot::load(&json).unwrap();
+    let from_toml = Snapshot::load(&toml).unwrap();
+
+    assert_eq!(from_json.repos, snapshot());
+    assert_eq!(from_json.user, None);
+    assert_eq!(from_toml.repos, snapshot());
+    assert_eq!(from_toml.user.as_deref(), Some("octocat"));
+}
+
+#[test]
+fn diff_command_prints_changes() {
+    let stand_in = StandIn::start(|request, base| paginated(request, base, 4));
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("old.json"), format::to_json(&snapshot()).unwrap()).unwrap();
+
+    let output = run(&stand_in, dir.path(), &["diff", "old.json", "-u", "octocat"]);
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(
+        lines,
+        [
+            "+ https://github.com/owner/repo-0 ★ 0",
+            "- https://github.com/owner/repo-9 ★ 9",
+            "~ https://github.com/owner/repo-1 description changed",
+            "~ https://github.com/owner/repo-2 stars 40 -> 2",
+            "1 added, 1 removed, 2 changed",
+        ]
+    );
+}
+
+#[test]
+fn diff_command_writes_json_for_the_toml_user() {
+    let stand_in = StandIn::start(|request, base| paginated(request, base, 4));
+    let dir = tempfile::tempdir().unwrap();
+    let toml = format::to_toml("hubot", Utc::now(), &snapshot()).unwrap();
+    fs::write(dir.path().join("old.toml"), toml).unwrap();
+
+    let output = run(&stand_in, dir.path(), &["diff", "old.toml", "-j", "-"]);
+
+    let diff: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(stand_in.requests()[0].path.starts_with("/users/hubot/starred"));
+    assert_eq!(diff["added"][0]["name"], "repo-0");
+    assert_eq!(diff["removed"][0]["name"], "repo-9");
+    assert_eq!(diff["changed"][1]["stars"], serde_json::json!({ "before": 40, "after": 2 }));
+    assert!(diff["changed"][1].get("description").is_none());
+}
+
+#[test]
+fn json_snapshot_needs_a_user() {
+    let stand_in = StandIn::start(|request, base| paginated(request, base, 4));
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("old.json"), "[]").unwrap();
+
+    let output = run(&stand_in, dir.path(), &["diff", "old.json"]);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stand_in.requests().is_empty());
+}
+
+#[test]
+fn snapshot_of_another_source_needs_a_user() {
+    let stand_in = StandIn::start(|request, base| paginated(request, base, 4));
+    let dir = tempfile::tempdir().unwrap();
+    run(&stand_in, dir.path(), &["--org", "rust-lang", "-t", "old.toml"]);
+
+    let output = run(&stand_in, dir.path(), &["diff", "old.toml"]);
+
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("The snapshot holds org:rust-lang, not a starred list"), "{}", stderr);
+    assert_eq!(stand_in.requests().len(), 1);
+
+    let output = run(&stand_in, dir.path(), &["diff", "old.toml", "-u", "octocat"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(stand_in.requests()[1].path.starts_with("/users/octocat/starred"));
+}
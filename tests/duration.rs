@@ -0,0 +1,28 @@
+use github_most_popular::duration::{format_duration, parse_duration};
+use github_most_popular::Error;
+
+use std::time::Duration;
+
+#[test]
+fn parses_amounts_with_units() {
+    assert_eq!(parse_duration("90s").unwrap(), Duration::from_secs(90));
+    assert_eq!(parse_duration(" 15m ").unwrap(), Duration::from_secs(15 * 60));
+    assert_eq!(parse_duration("2w").unwrap(), Duration::from_secs(2 * 7 * 24 * 60 * 60));
+    assert!(parse_duration("1y").is_err());
+    assert!(parse_duration("h").is_err());
+}
+
+#[test]
+fn overflowing_duration_is_invalid() {
+    let err = parse_duration("99999999999999999w").unwrap_err();
+
+    assert!(matches!(err, Error::Invalid(_)), "{:?}", err);
+    assert_eq!(parse_duration("18446744073709551615s").unwrap(), Duration::from_secs(u64::MAX));
+}
+
+#[test]
+fn formats_the_two_largest_units() {
+    assert_eq!(format_duration(Duration::from_secs(3 * 24 * 60 * 60 + 2 * 60 * 60 + 5)), "3d 2h");
+    assert_eq!(format_duration(Duration::from_secs(5 * 60)), "5m");
+    assert_eq!(format_duration(Duration::ZERO), "0s");
+}
@@ -0,0 +1,102 @@
+mod common;
+
+use common::{paginated, run, Response, StandIn};
+
+use github_most_popular::{Error, StarredClient};
+
+use std::time::Duration;
+
+fn client(stand_in: &StandIn) -> StarredClient {
+    StarredClient::new()
+        .with_api_url(stand_in.url())
+        .with_token("test-token")
+        .with_retries(0, Duration::ZERO)
+}
+
+#[test]
+fn statuses_map_to_error_kinds() {
+    let missing = StandIn::start(|_, _| Response::status(404));
+    let unauthorized = StandIn::start(|_, _| Response::status(401));
+    let broken = StandIn::start(|_, _| Response::status(500));
+    let garbled = StandIn::start(|_, _| Response::json("{\"message\": \"not a list\"}"));
+
+    let err = client(&missing).starred("octocat", None).unwrap_err();
+    assert!(matches!(&err, Error::UserNotFound(user) if user == "octocat"), "{:?}", err);
+    let err = client(&unauthorized).starred("octocat", None).unwrap_err();
+    assert!(matches!(err, Error::Auth { status: 401 }), "{:?}", err);
+    let err = client(&broken).starred("octocat", None).unwrap_err();
+    assert!(matches!(err, Error::Status { status: 500, .. }), "{:?}", err);
+    let err = client(&garbled).starred("octocat", None).unwrap_err();
+    assert!(matches!(err, Error::Parse { .. }), "{:?}", err);
+    let err = client(&garbled).starred("-octocat", None).unwrap_err();
+    assert!(matches!(err, Error::Invalid(_)), "{:?}", err);
+}
+
+#[test]
+fn unreachable_api_is_a_network_error() {
+    let client = StarredClient::new()
+        .with_api_url("http://127.0.0.1:1")
+        .with_retries(0, Duration::ZERO);
+
+    let err = client.starred("octocat", None).unwrap_err();
+
+    assert!(matches!(err, Error::Network(_)), "{:?}", err);
+    assert_eq!(err.exit_code(), 6);
+}
+
+#[test]
+fn failures_exit_with_their_code_on_stderr() {
+    let stand_in = StandIn::start(|_, _| Response::status(404));
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = run(&stand_in, dir.path(), &["-u", "octocat"]);
+
+    assert_eq!(output.status.code(), Some(3));
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.starts_with("ERROR: GitHub user octocat does not exist"), "{}", stderr);
+}
+
+#[test]
+fn invalid_input_exits_with_2() {
+    let stand_in = StandIn::start(|request, base| paginated(request, base, 3));
+    let dir = tempfile::tempdir().unwrap();
+
+    for args in [
+        &["-u", "octocat", "--sort", "size"][..],
+        &["-u", "octocat", "--limit", "many"],
+        &["-u", "octocat", "--max-age", "1y"],
+        &["-u", "octocat", "--max-age", "99999999999999999w"],
+        &["-u", "octocat", "--starred-within", "100000000w"],
+        &["--json", "out.json"],
+    ] {
+        let output = run(&stand_in, dir.path(), args);
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+        assert!(!output.stderr.is_empty(), "{:?}", args);
+    }
+    assert!(stand_in.requests().is_empty());
+}
+
+#[test]
+fn failed_export_is_an_io_error() {
+    let stand_in = StandIn::start(|request, base| paginated(request, base, 3));
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = run(&stand_in, dir.path(), &["-u", "octocat", "-j", "missing/out.json"]);
+
+    assert_eq!(output.status.code(), Some(8));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Could not write missing/out.json"), "{}", stderr);
+    assert!(stderr.contains("caused by:"), "{}", stderr);
+}
+
+#[test]
+fn success_exits_with_0() {
+    let stand_in = StandIn::start(|request, base| paginated(request, base, 3));
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = run(&stand_in, dir.path(), &["-u", "octocat", "-j", "out.json"]);
+
+    assert_eq!(output.status.code(), Some(0));
+    assert!(output.stderr.is_empty());
+}
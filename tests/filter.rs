@@ -0,0 +1,79 @@
+mod common;
+
+use common::{run, starred_fixture, Response, StandIn, STARRED_JSON};
+
+use github_most_popular::{Filter, Repo};
+
+fn names(repos: &[Repo]) -> Vec<&str> {
+    repos.iter().map(|repo| repo.name.as_str()).collect()
+}
+
+#[test]
+fn default_filter_keeps_everything() {
+    assert_eq!(Filter::default().apply(starred_fixture()).len(), 10);
+}
+
+#[test]
+fn criteria_compose() {
+    let filter = Filter {
+        language: Some("emacs lisp".into()),
+        owner: Some("TECOSAUR".into()),
+        min_stars: Some(70),
+        ..Filter::default()
+    };
+
+    assert_eq!(names(&filter.apply(starred_fixture())), ["engrave-faces"]);
+}
+
+#[test]
+fn topic_matches_any_tag() {
+    let filter = Filter {
+        topic: Some("emacs".into()),
+        ..Filter::default()
+    };
+
+    assert_eq!(
+        names(&filter.apply(starred_fixture())),
+        ["engrave-faces", "emacs-config", "copy-as-org-mode"]
+    );
+}
+
+#[test]
+fn excludes_forks_and_archived() {
+    let mut repos = starred_fixture();
+    repos[0].archived = true;
+    let filter = Filter {
+        exclude_archived: true,
+        exclude_forks: true,
+        ..Filter::default()
+    };
+
+    let kept = filter.apply(repos);
+    assert_eq!(kept.len(), 8);
+    assert!(!names(&kept).contains(&"elpaca"));
+    assert!(!names(&kept).contains(&"elisp-guide"));
+}
+
+#[test]
+fn language_filter_skips_repos_without_language() {
+    let filter = Filter {
+        language: Some("Nix".into()),
+        ..Filter::default()
+    };
+
+    assert_eq!(names(&filter.apply(starred_fixture())), ["nix-emacs-ci"]);
+}
+
+#[test]
+fn filters_apply_to_exports() {
+    let stand_in = StandIn::start(|_, _| Response::json(STARRED_JSON));
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = run(&stand_in, dir.path(), &["-u", "hlissner", "--language", "emacs lisp", "-j", "out.json"]);
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let json = std::fs::read_to_string(dir.path().join("out.json")).unwrap();
+    let repos: Vec<Repo> = serde_json::from_str(&json).unwrap();
+    assert_eq!(repos.len(), 4);
+    assert!(repos.iter().all(|repo| repo.language.as_deref() == Some("Emacs Lisp")));
+}
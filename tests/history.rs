@@ -0,0 +1,201 @@
+mod common;
+
+use common::{paginated, repo_json, run, Response, StandIn};
+
+use github_most_popular::{FileCache, History, Repo, StarredClient};
+
+use chrono::{Duration as ChronoDuration, TimeZone, Utc};
+
+use std::path::Path;
+
+fn repo(i: usize, stars: u64) -> Repo {
+    let mut repo: Repo = serde_json::from_value(repo_json(i)).unwrap();
+    repo.full_name = format!("owner/repo-{}", i);
+    repo.star_count = stars;
+    repo
+}
+
+/// octocat stars repo 1 and 2, repo 2 gains stars, then repo 1 is unstarred for repo 3
+fn recorded(dir: &Path) -> History {
+    let history = History::open(&dir.join("history.sqlite")).unwrap();
+    let day = |d| Utc.with_ymd_and_hms(2022, 10, d, 12, 0, 0).unwrap();
+    history.record("octocat", day(1), &[repo(1, 10), repo(2, 20)]).unwrap();
+    history.record("OctoCat", day(2), &[repo(1, 10), repo(2, 25)]).unwrap();
+    history.record("hubot", day(2), &[repo(2, 25)]).unwrap();
+    history.record("octocat", day(3), &[repo(3, 5), repo(2, 27)]).unwrap();
+    history
+}
+
+#[test]
+fn trend_follows_a_repo_across_fetches() {
+    let dir = tempfile::tempdir().unwrap();
+    let history = recorded(dir.path());
+
+    let by_name = history.trend("Owner/Repo-2").unwrap();
+    let by_url = history.trend("https://github.com/owner/repo-2").unwrap();
+
+    let stars: Vec<_> = by_name.iter().map(|sample| sample.star_count).collect();
+    assert_eq!(stars, [20, 25, 25, 27]);
+    assert_eq!(by_name, by_url);
+    assert!(history.trend("owner/unknown").unwrap().is_empty());
+}
+
+#[test]
+fn fetches_of_a_user() {
+    let dir = tempfile::tempdir().unwrap();
+    let history = recorded(dir.path());
+
+    let fetches = history.fetches("octocat").unwrap();
+
+    let sizes: Vec<_> = fetches
+        .iter()
+        .map(|fetch| (fetch.fetched_at.format("%d").to_string(), fetch.repo_count, fetch.star_total))
+        .collect();
+    assert_eq!(
+        sizes,
+        [("01".to_string(), 2, 30), ("02".to_string(), 2, 35), ("03".to_string(), 2, 32)]
+    );
+}
+
+#[test]
+fn seen_marks_unstarred_repos() {
+    let dir = tempfile::tempdir().unwrap();
+    let history = recorded(dir.path());
+
+    let seen = history.seen("octocat").unwrap();
+
+    let rows: Vec<_> = seen
+        .iter()
+        .map(|repo| {
+            (
+                repo.full_name.as_str(),
+                repo.first_seen.format("%d").to_string(),
+                repo.last_seen.format("%d").to_string(),
+                repo.star_count,
+                repo.starred,
+            )
+        })
+        .collect();
+    assert_eq!(
+        rows,
+        [
+            ("owner/repo-3", "03".to_string(), "03".to_string(), 5, true),
+            ("owner/repo-1", "01".to_string(), "02".to_string(), 10, false),
+            ("owner/repo-2", "01".to_string(), "03".to_string(), 27, true),
+        ]
+    );
+}
+
+#[test]
+fn history_survives_reopening() {
+    let dir = tempfile::tempdir().unwrap();
+    drop(recorded(dir.path()));
+
+    let history = History::open(&dir.path().join("history.sqlite")).unwrap();
+
+    assert_eq!(history.fetches("octocat").unwrap().len(), 3);
+}
+
+#[test]
+fn client_records_network_fetches_only() {
+    let stand_in = StandIn::start(|request, base| paginated(request, base, 3));
+    let dir = tempfile::tempdir().unwrap();
+    let client = || {
+        StarredClient::new()
+            .with_api_url(stand_in.url())
+            .with_cache(FileCache::new(dir.path().join("cache")))
+            .with_history(History::open(&dir.path().join("history.sqlite")).unwrap())
+    };
+
+    client().starred("octocat", None).unwrap();
+    client().starred("octocat", None).unwrap();
+    client().starred("octocat", Some(1)).unwrap();
+    client().with_max_age(std::time::Duration::ZERO).starred("octocat", None).unwrap();
+
+    let fetches = History::open(&dir.path().join("history.sqlite"))
+        .unwrap()
+        .fetches("octocat")
+        .unwrap();
+    assert_eq!(fetches.len(), 2);
+    assert_eq!(fetches[0].repo_count, 3);
+    assert!(Utc::now() - fetches[1].fetched_at < ChronoDuration::minutes(1));
+}
+
+#[test]
+fn client_skips_lists_revalidated_with_304() {
+    let stand_in = StandIn::start(|request, _| {
+        if request.header("If-None-Match") == Some("W/\"v1\"") {
+            return Response::status(304);
+        }
+        Response::json(serde_json::json!([repo_json(1), repo_json(2)]).to_string()).header("ETag", "W/\"v1\"")
+    });
+    let dir = tempfile::tempdir().unwrap();
+    let client = StarredClient::new()
+        .with_api_url(stand_in.url())
+        .with_cache(FileCache::new(dir.path().join("cache")))
+        .with_history(History::open(&dir.path().join("history.sqlite")).unwrap())
+        .with_max_age(std::time::Duration::ZERO);
+
+    client.starred("octocat", None).unwrap();
+    client.starred("octocat", None).unwrap();
+
+    assert_eq!(stand_in.requests()[1].header("If-None-Match"), Some("W/\"v1\""));
+    let history = History::open(&dir.path().join("history.sqlite")).unwrap();
+    assert_eq!(history.fetches("octocat").unwrap().len(), 1);
+}
+
+#[test]
+fn history_command_reports_recorded_fetches() {
+    let stand_in = StandIn::start(|request, base| paginated(request, base, 3));
+    let dir = tempfile::tempdir().unwrap();
+
+    run(&stand_in, dir.path(), &["-u", "octocat", "-j", "out.json"]);
+    run(&stand_in, dir.path(), &["-u", "octocat", "-j", "out.json", "--refresh"]);
+    let user = run(&stand_in, dir.path(), &["history", "user", "-u", "octocat", "-j", "-"]);
+    let trend = run(&stand_in, dir.path(), &["history", "trend", "https://github.com/owner/repo-2"]);
+    let seen = run(&stand_in, dir.path(), &["history", "seen", "-u", "octocat"]);
+
+    let fetches: serde_json::Value = serde_json::from_slice(&user.stdout).unwrap();
+    assert_eq!(fetches.as_array().unwrap().len(), 2);
+    assert_eq!(fetches[0]["repo_count"], 3);
+    assert_eq!(fetches[0]["star_total"], 3);
+
+    let trend = String::from_utf8(trend.stdout).unwrap();
+    let lines: Vec<_> = trend.lines().collect();
+    assert!(lines[0].starts_with("FETCHED"), "{}", trend);
+    assert_eq!(lines.len(), 3, "{}", trend);
+    assert!(lines[2].ends_with("2      +0       0       0"), "{}", trend);
+
+    let seen = String::from_utf8(seen.stdout).unwrap();
+    assert_eq!(seen.lines().count(), 4, "{}", seen);
+}
+
+#[test]
+fn no_history_leaves_the_data_dir_alone() {
+    let stand_in = StandIn::start(|request, base| paginated(request, base, 3));
+    let dir = tempfile::tempdir().unwrap();
+
+    run(&stand_in, dir.path(), &["-u", "octocat", "-j", "out.json", "--no-history"]);
+
+    assert!(dir.path().join("out.json").exists());
+    assert!(!dir.path().join("data").exists());
+}
+
+#[test]
+fn unusable_data_dir_only_warns() {
+    let stand_in = StandIn::start(|request, base| paginated(request, base, 3));
+    let dir = tempfile::tempdir().unwrap();
+    // a file where the data dir should be
+    std::fs::write(dir.path().join("data"), "").unwrap();
+
+    let online = run(&stand_in, dir.path(), &["-u", "octocat", "-j", "out.json"]);
+    let offline = run(&stand_in, dir.path(), &["-u", "octocat", "--offline", "-j", "offline.json"]);
+
+    for output in [online, offline] {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(output.status.success(), "{}", stderr);
+        assert!(stderr.contains("WARNING: Could not create history dir"), "{}", stderr);
+    }
+    assert!(dir.path().join("offline.json").exists());
+    assert_eq!(stand_in.requests().len(), 1);
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use common::starred_fixture;
+
+use github_most_popular::format::{self, GroupBy};
+
+#[test]
+fn groups_by_language_with_table_of_contents() {
+    let markdown = format::to_markdown("Stars", &starred_fixture(), GroupBy::Language);
+
+    assert!(markdown.starts_with("# Stars\n\n- [Emacs Lisp](#emacs-lisp) (4)\n- [Nix](#nix) (1)\n"));
+    assert!(markdown.contains("- [Other](#other) (2)\n"));
+    assert!(markdown.contains(
+        "\n## Nix\n\n- [purcell/nix-emacs-ci](https://github.com/purcell/nix-emacs-ci) \
+         ![Stars](https://img.shields.io/github/stars/purcell/nix-emacs-ci?style=social) \
+         - Emacs installations for continuous integration\n"
+    ));
+    // sections other than Other are alphabetical, Other comes last
+    let emacs_lisp = markdown.find("## Emacs Lisp").unwrap();
+    let typescript = markdown.find("## TypeScript").unwrap();
+    assert!(emacs_lisp < typescript && typescript < markdown.find("## Other").unwrap());
+}
+
+#[test]
+fn topic_grouping_lists_repo_under_each_topic() {
+    let markdown = format::to_markdown("Stars", &starred_fixture(), GroupBy::Topic);
+
+    assert_eq!(markdown.matches("[tecosaur/emacs-config](").count(), 4);
+    assert!(markdown.contains("- [emacs](#emacs) (3)\n"));
+}
+
+#[test]
+fn repeated_anchors_get_suffixed() {
+    let mut repos = starred_fixture();
+    repos[0].language = Some("C".into());
+    repos[1].language = Some("C++".into());
+    repos[2].language = Some("C#".into());
+
+    let markdown = format::to_markdown("Stars", &repos, GroupBy::Language);
+    assert!(markdown.contains("- [C](#c) (1)\n- [C#](#c-1) (1)\n- [C++](#c-2) (1)\n"));
+}
+
+#[test]
+fn absent_description_leaves_entry_short() {
+    let mut repos = starred_fixture();
+    repos.truncate(1);
+    repos[0].description = None;
+
+    let markdown = format::to_markdown("Stars", &repos, GroupBy::Language);
+    assert!(markdown.ends_with("?style=social)\n"));
+}
@@ -0,0 +1,52 @@
+use github_most_popular::{format, Repo};
+
+use chrono::Utc;
+
+fn fixture() -> Vec<Repo> {
+    serde_json::from_str(include_str!("fixtures/nulls.json")).expect("nulls fixture parses")
+}
+
+#[test]
+fn parses_null_and_missing_fields() {
+    let repos = fixture();
+
+    assert_eq!(repos.len(), 3);
+    assert_eq!(repos[0].description, None);
+    assert_eq!(repos[0].license, None);
+    assert_eq!(repos[1].description(), None);
+    assert_eq!(repos[1].homepage(), None);
+    assert_eq!(repos[1].language.as_deref(), Some("Rust"));
+    assert_eq!(repos[1].license_id(), Some("MIT"));
+    assert_eq!(repos[2].star_count, 0);
+    assert_eq!(repos[2].homepage, None);
+}
+
+#[test]
+fn terminal_list_renders_absent_description() {
+    colored::control::set_override(false);
+    let mut out = Vec::new();
+    format::write_list(&mut out, &fixture()).unwrap();
+    let out = String::from_utf8(out).unwrap();
+
+    assert_eq!(out.matches("Description: No description").count(), 3);
+}
+
+#[test]
+fn exports_absent_values() {
+    let repos = fixture();
+
+    let json: Vec<serde_json::Value> =
+        serde_json::from_str(&format::to_json(&repos).unwrap()).unwrap();
+    assert!(json[0]["description"].is_null());
+    assert_eq!(json[1]["license"]["spdx_id"], "MIT");
+    // an exported list reads back in
+    let reimported: Vec<Repo> = serde_json::from_value(serde_json::Value::Array(json)).unwrap();
+    assert_eq!(reimported, repos);
+
+    let toml = format::to_toml("owner", Utc::now(), &repos).unwrap();
+    let first = toml.split("[[repos]]").nth(1).unwrap();
+    assert!(first.contains("name = \"no-description\""));
+    assert!(!first.contains("description ="));
+    assert!(!first.contains("license ="));
+    assert_eq!(format::from_toml(&toml).unwrap().repos, repos);
+}
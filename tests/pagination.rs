@@ -0,0 +1,53 @@
+mod common;
+
+use common::{cached_file, paginated, run, StandIn};
+
+use std::fs;
+use std::path::Path;
+
+fn exported(dir: &Path) -> Vec<serde_json::Value> {
+    serde_json::from_str(&fs::read_to_string(dir.join("out.json")).unwrap()).unwrap()
+}
+
+#[test]
+fn follows_next_links_until_exhausted() {
+    let stand_in = StandIn::start(|request, base| paginated(request, base, 250));
+    let dir = tempfile::tempdir().unwrap();
+
+    run(&stand_in, dir.path(), &["-u", "octocat", "-j", "out.json"]);
+
+    assert_eq!(exported(dir.path()).len(), 250);
+    let requests = stand_in.requests();
+    assert_eq!(requests.len(), 3);
+    assert_eq!(requests[0].path, "/users/octocat/starred?per_page=100");
+    assert!(requests.iter().all(|r| r.query("per_page") == Some("100")));
+}
+
+#[test]
+fn limit_stops_fetching_early() {
+    let stand_in = StandIn::start(|request, base| paginated(request, base, 250));
+    let dir = tempfile::tempdir().unwrap();
+
+    run(&stand_in, dir.path(), &["-u", "octocat", "-l", "150", "-j", "out.json"]);
+
+    assert_eq!(exported(dir.path()).len(), 150);
+    assert_eq!(stand_in.requests().len(), 2);
+    // a truncated list must not end up in the cache
+    assert!(cached_file(&dir.path().join("cache"), "octocat").is_none());
+}
+
+#[test]
+fn cache_stores_merged_pages() {
+    let stand_in = StandIn::start(|request, base| paginated(request, base, 120));
+    let dir = tempfile::tempdir().unwrap();
+
+    run(&stand_in, dir.path(), &["-u", "octocat", "-j", "out.json"]);
+    let cached: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(cached_file(&dir.path().join("cache"), "octocat").unwrap()).unwrap())
+            .unwrap();
+    assert_eq!(cached["repos"].as_array().unwrap().len(), 120);
+
+    run(&stand_in, dir.path(), &["-u", "octocat", "-l", "5", "-j", "out.json"]);
+    assert_eq!(exported(dir.path()).len(), 5);
+    assert_eq!(stand_in.requests().len(), 2);
+}
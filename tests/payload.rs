@@ -0,0 +1,40 @@
+mod common;
+
+use common::starred_fixture;
+
+use github_most_popular::{format, Repo};
+
+use chrono::{Datelike, TimeZone, Utc};
+
+#[test]
+fn parses_full_payload() {
+    let repos = starred_fixture();
+    let elpaca = &repos[0];
+
+    assert_eq!(elpaca.id, 443592401);
+    assert_eq!(elpaca.full_name, "progfolio/elpaca");
+    assert_eq!(elpaca.owner.login, "progfolio");
+    assert_eq!(elpaca.owner.kind, "User");
+    assert_eq!(elpaca.language.as_deref(), Some("Emacs Lisp"));
+    assert_eq!(elpaca.license_id(), Some("GPL-3.0"));
+    assert_eq!(elpaca.fork_count, 5);
+    assert_eq!(elpaca.open_issue_count, 3);
+    assert!(!elpaca.archived && !elpaca.fork);
+    assert_eq!(
+        elpaca.created_at,
+        Some(Utc.with_ymd_and_hms(2022, 1, 1, 17, 49, 54).unwrap())
+    );
+    assert_eq!(elpaca.pushed_at.unwrap().month(), 10);
+}
+
+#[test]
+fn exports_round_trip_full_payload() {
+    let repos = starred_fixture();
+
+    let reimported: Vec<Repo> = serde_json::from_str(&format::to_json(&repos).unwrap()).unwrap();
+    assert_eq!(reimported, repos);
+
+    let toml = format::to_toml("hlissner", Utc::now(), &repos).unwrap();
+    assert!(toml.contains("full_name = \"progfolio/elpaca\""));
+    assert!(toml.contains("created_at = \"2022-01-01T17:49:54Z\""));
+}
@@ -0,0 +1,118 @@
+mod common;
+
+use common::{paginated, run, Request, Response, StandIn};
+
+use github_most_popular::Popular;
+
+use chrono::NaiveDate;
+
+use std::fs;
+use std::path::Path;
+use std::process::Output;
+
+/// `paginated` wrapped in a search result object
+fn search_results(request: &Request, base: &str, total: usize) -> Response {
+    let mut response = paginated(request, base, total);
+    let items: serde_json::Value = serde_json::from_str(&response.body).unwrap();
+    response.body = serde_json::json!({ "total_count": total, "items": items }).to_string();
+    response
+}
+
+/// run the `popular` subcommand with args
+fn run_popular(stand_in: &StandIn, dir: &Path, args: &[&str]) -> Output {
+    run(stand_in, dir, &[&["popular"], args].concat())
+}
+
+#[test]
+fn builds_the_search_query() {
+    assert_eq!(Popular::default().query(), "stars:>=1");
+
+    let popular = Popular {
+        language: Some("Visual Basic".to_string()),
+        topics: vec!["cli".to_string(), "tui".to_string()],
+        created_after: NaiveDate::from_ymd_opt(2024, 1, 31),
+        min_stars: Some(1000),
+    };
+    assert_eq!(
+        popular.query(),
+        "stars:>=1000 language:\"Visual Basic\" topic:cli topic:tui created:>=2024-01-31"
+    );
+}
+
+#[test]
+fn searches_by_stars_with_the_filters() {
+    let stand_in = StandIn::start(|request, base| search_results(request, base, 3));
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = run_popular(
+        &stand_in,
+        dir.path(),
+        &[
+            "--language", "rust", "--topic", "cli", "--created-after", "2024-01-31", "--min-stars", "500", "-j",
+            "out.json",
+        ],
+    );
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let request = &stand_in.requests()[0];
+    assert!(request.path.starts_with("/search/repositories"));
+    assert_eq!(
+        request.query("q"),
+        Some("stars%3A%3E%3D500+language%3Arust+topic%3Acli+created%3A%3E%3D2024-01-31")
+    );
+    assert_eq!(request.query("sort"), Some("stars"));
+    assert_eq!(request.query("order"), Some("desc"));
+    let repos: serde_json::Value = serde_json::from_str(&fs::read_to_string(dir.path().join("out.json")).unwrap()).unwrap();
+    assert_eq!(repos.as_array().unwrap().len(), 3);
+}
+
+#[test]
+fn paginates_up_to_the_limit() {
+    let stand_in = StandIn::start(|request, base| search_results(request, base, 250));
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = run_popular(&stand_in, dir.path(), &["-l", "150", "-j", "out.json"]);
+
+    assert!(output.status.success());
+    assert_eq!(stand_in.requests().len(), 2);
+    let repos: serde_json::Value = serde_json::from_str(&fs::read_to_string(dir.path().join("out.json")).unwrap()).unwrap();
+    assert_eq!(repos.as_array().unwrap().len(), 150);
+    // kept in the order github ranks them
+    assert_eq!(repos[0]["name"], "repo-0");
+}
+
+#[test]
+fn stops_at_the_search_result_limit() {
+    let stand_in = StandIn::start(|request, base| search_results(request, base, 1200));
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = run_popular(&stand_in, dir.path(), &["-l", "5000", "--csv", "out.csv"]);
+
+    assert!(output.status.success());
+    assert_eq!(stand_in.requests().len(), 10);
+    let csv = fs::read_to_string(dir.path().join("out.csv")).unwrap();
+    assert_eq!(csv.lines().count(), 1001);
+}
+
+#[test]
+fn lists_to_the_terminal_by_default() {
+    let stand_in = StandIn::start(|request, base| search_results(request, base, 2));
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = run_popular(&stand_in, dir.path(), &[]);
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("https://github.com/owner/repo-1"), "{}", stdout);
+    assert_eq!(stand_in.requests()[0].query("q"), Some("stars%3A%3E%3D1"));
+}
+
+#[test]
+fn invalid_date_is_rejected_before_searching() {
+    let stand_in = StandIn::start(|request, base| search_results(request, base, 2));
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = run_popular(&stand_in, dir.path(), &["--created-after", "last year"]);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stand_in.requests().is_empty());
+}
@@ -0,0 +1,174 @@
+mod common;
+
+use common::{paginated, run, Response, StandIn};
+
+use github_most_popular::{Error, StarredClient};
+
+use chrono::Utc;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+fn client(stand_in: &StandIn) -> StarredClient {
+    StarredClient::new()
+        .with_api_url(stand_in.url())
+        .with_token("test-token")
+        .with_retries(2, Duration::from_millis(1))
+}
+
+/// answer the first `failures` requests with response, then serve 3 repos
+fn failing(failures: usize, response: fn() -> Response) -> StandIn {
+    let seen = AtomicUsize::new(0);
+    StandIn::start(move |request, base| {
+        if seen.fetch_add(1, Ordering::SeqCst) < failures {
+            response()
+        } else {
+            paginated(request, base, 3)
+        }
+    })
+}
+
+fn exhausted() -> Response {
+    Response::status(403)
+        .header("X-RateLimit-Limit", "5000")
+        .header("X-RateLimit-Remaining", "0")
+        .header("X-RateLimit-Reset", (Utc::now().timestamp() + 600).to_string())
+}
+
+#[test]
+fn retries_server_errors() {
+    let stand_in = failing(2, || Response::status(502));
+
+    let repos = client(&stand_in).starred("octocat", None).unwrap();
+
+    assert_eq!(repos.len(), 3);
+    assert_eq!(stand_in.requests().len(), 3);
+}
+
+#[test]
+fn gives_up_after_the_last_retry() {
+    let stand_in = failing(usize::MAX, || Response::status(500));
+
+    let err = client(&stand_in).starred("octocat", None).unwrap_err();
+
+    assert!(err.to_string().contains("status 500"), "{}", err);
+    assert_eq!(stand_in.requests().len(), 3);
+}
+
+#[test]
+fn stops_when_rate_limit_is_exhausted() {
+    let stand_in = failing(usize::MAX, exhausted);
+
+    let err = client(&stand_in).starred("octocat", None).unwrap_err();
+
+    assert!(err.to_string().contains("rate limit exceeded, it resets in 9m"), "{}", err);
+    assert_eq!(stand_in.requests().len(), 1);
+}
+
+#[test]
+fn waits_for_retry_after_if_allowed() {
+    let stand_in = failing(1, || Response::status(429).header("Retry-After", "1"));
+    let waits = Rc::new(RefCell::new(Vec::new()));
+
+    let repos = client(&stand_in)
+        .with_wait_for_rate_limit(true)
+        .on_rate_limit_wait({
+            let waits = Rc::clone(&waits);
+            move |wait| waits.borrow_mut().push(wait)
+        })
+        .starred("octocat", None)
+        .unwrap();
+
+    assert_eq!(repos.len(), 3);
+    assert_eq!(stand_in.requests().len(), 2);
+    assert_eq!(*waits.borrow(), [Duration::from_secs(1)]);
+}
+
+#[test]
+fn waits_at_least_the_minimum_and_gives_up_eventually() {
+    // reset already past on the local clock, as with a clock running behind github's
+    let stand_in = failing(usize::MAX, || {
+        Response::status(403)
+            .header("X-RateLimit-Limit", "5000")
+            .header("X-RateLimit-Remaining", "0")
+            .header("X-RateLimit-Reset", (Utc::now().timestamp() - 60).to_string())
+    });
+    let started = Instant::now();
+
+    let err = client(&stand_in)
+        .with_wait_for_rate_limit(true)
+        .with_min_rate_limit_wait(Duration::from_millis(20))
+        .starred("octocat", None)
+        .unwrap_err();
+
+    assert!(matches!(err, Error::RateLimited { .. }), "{:?}", err);
+    assert_eq!(stand_in.requests().len(), 4);
+    assert!(started.elapsed() >= Duration::from_millis(60));
+}
+
+#[test]
+fn forbidden_is_not_mistaken_for_a_rate_limit() {
+    let stand_in = failing(usize::MAX, || {
+        Response::status(403).header("X-RateLimit-Remaining", "4999")
+    });
+
+    let err = client(&stand_in)
+        .with_wait_for_rate_limit(true)
+        .starred("octocat", None)
+        .unwrap_err();
+
+    assert!(err.to_string().contains("status 403"), "{}", err);
+    assert_eq!(stand_in.requests().len(), 1);
+}
+
+fn rate_limit_json() -> String {
+    let reset = Utc::now().timestamp() + 1800;
+    serde_json::json!({
+        "resources": {
+            "core": { "limit": 5000, "remaining": 4990, "used": 10, "reset": reset },
+            "search": { "limit": 30, "remaining": 0, "used": 30, "reset": reset },
+        },
+        "rate": { "limit": 5000, "remaining": 4990, "used": 10, "reset": reset },
+    })
+    .to_string()
+}
+
+#[test]
+fn reads_quota_of_every_resource() {
+    let stand_in = StandIn::start(|_, _| Response::json(rate_limit_json()));
+
+    let limits = client(&stand_in).rate_limit().unwrap();
+
+    assert_eq!(stand_in.requests()[0].path, "/rate_limit");
+    assert_eq!(limits.resources["core"].remaining, 4990);
+    assert_eq!(limits.resources["search"].used, 30);
+    assert!(limits.resources["core"].reset_in() > Duration::from_secs(1700));
+}
+
+#[test]
+fn rate_limit_command_lists_resources() {
+    let stand_in = StandIn::start(|_, _| Response::json(rate_limit_json()));
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = run(&stand_in, dir.path(), &["rate-limit", "--anonymous"]);
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<_> = stdout.lines().collect();
+    assert!(lines[0].starts_with("RESOURCE"), "{}", stdout);
+    assert!(lines[1].starts_with("core") && lines[1].contains("4990"), "{}", stdout);
+    assert!(lines[2].starts_with("search") && lines[2].contains("29m"), "{}", stdout);
+    assert!(stand_in.requests()[0].header("Authorization").is_none());
+}
+
+#[test]
+fn rate_limit_command_fails_offline() {
+    let stand_in = StandIn::start(|_, _| Response::json(rate_limit_json()));
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = run(&stand_in, dir.path(), &["--offline", "rate-limit"]);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stand_in.requests().is_empty());
+}
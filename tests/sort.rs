@@ -0,0 +1,60 @@
+mod common;
+
+use common::starred_fixture;
+
+use github_most_popular::{Order, Sort};
+
+fn sorted(keys: &str, order: Option<Order>) -> Vec<String> {
+    let mut repos = starred_fixture();
+    Sort::parse(keys, order).unwrap().apply(&mut repos);
+    repos.into_iter().map(|repo| repo.name).collect()
+}
+
+#[test]
+fn default_is_most_stars_first() {
+    let mut repos = starred_fixture();
+    Sort::default().apply(&mut repos);
+
+    assert_eq!(repos[0].name, "emacs-config");
+    assert!(repos.windows(2).all(|w| w[0].star_count >= w[1].star_count));
+}
+
+#[test]
+fn direction_overrides_key_default() {
+    assert_eq!(sorted("stars", Some(Order::Asc))[0], "elisp-guide");
+    assert_eq!(sorted("name", None)[0], "copy-as-org-mode");
+    assert_eq!(sorted("name", Some(Order::Desc))[0], "zgenom");
+}
+
+#[test]
+fn later_keys_break_ties() {
+    let names = sorted("language,stars", None);
+
+    // Emacs Lisp by stars, then Nix, Org, Shell, TypeScript, repos without language last
+    assert_eq!(
+        names,
+        [
+            "orderless",
+            "elpaca",
+            "engrave-faces",
+            "org-glossary",
+            "nix-emacs-ci",
+            "emacs-config",
+            "zgenom",
+            "copy-as-org-mode",
+            "doom-icon",
+            "elisp-guide",
+        ]
+    );
+}
+
+#[test]
+fn absent_values_sort_last_in_both_directions() {
+    assert_eq!(sorted("language", Some(Order::Desc)).last().unwrap(), "elisp-guide");
+    assert_eq!(sorted("language", Some(Order::Asc)).last().unwrap(), "elisp-guide");
+}
+
+#[test]
+fn rejects_unknown_keys() {
+    assert!(Sort::parse("stars,popularity", None).is_err());
+}
@@ -0,0 +1,178 @@
+mod common;
+
+use common::{paginated, repo_json, run, Response, StandIn};
+
+use github_most_popular::Source;
+
+use std::fs;
+use std::path::Path;
+
+fn names(dir: &Path) -> Vec<String> {
+    let json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(dir.join("out.json")).unwrap()).unwrap();
+    json.as_array()
+        .unwrap()
+        .iter()
+        .map(|repo| repo["name"].as_str().unwrap().to_string())
+        .collect()
+}
+
+fn paths(stand_in: &StandIn) -> Vec<String> {
+    stand_in
+        .requests()
+        .into_iter()
+        .map(|request| request.path.split('?').next().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn repos_list_skips_comments_and_accepts_urls() {
+    let list = "\
+# tools
+rust-lang/rust
+
+https://github.com/BurntSushi/ripgrep/
+  sharkdp/bat.git
+";
+
+    assert_eq!(
+        Source::repos_from_list(list).unwrap(),
+        Source::Repos(vec![
+            "rust-lang/rust".to_string(),
+            "BurntSushi/ripgrep".to_string(),
+            "sharkdp/bat".to_string(),
+        ])
+    );
+    assert!(Source::repos_from_list("rust-lang").is_err());
+    assert!(Source::repos_from_list("rust-lang/rust/issues").is_err());
+    assert!(Source::repos_from_list("-bad/name").is_err());
+    assert!(Source::Search(" ".to_string()).validate().is_err());
+}
+
+#[test]
+fn lists_the_repos_of_an_org() {
+    let stand_in = StandIn::start(|request, base| paginated(request, base, 3));
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = run(&stand_in, dir.path(), &["--org", "rust-lang", "-j", "out.json"]);
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(paths(&stand_in), ["/orgs/rust-lang/repos"]);
+    assert_eq!(names(dir.path()).len(), 3);
+}
+
+#[test]
+fn lists_the_repos_a_user_owns() {
+    let stand_in = StandIn::start(|request, base| paginated(request, base, 2));
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = run(&stand_in, dir.path(), &["--owned-by", "octocat", "-j", "out.json"]);
+
+    assert!(output.status.success());
+    let requests = stand_in.requests();
+    assert!(requests[0].path.starts_with("/users/octocat/repos"));
+    assert_eq!(requests[0].query("type"), Some("owner"));
+}
+
+#[test]
+fn unknown_org_exits_with_not_found() {
+    let stand_in = StandIn::start(|_, _| Response::status(404));
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = run(&stand_in, dir.path(), &["--org", "nobody", "-j", "out.json"]);
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("nobody"), "{}", stderr);
+    assert_eq!(output.status.code(), Some(3));
+}
+
+#[test]
+fn search_results_are_unwrapped() {
+    let stand_in = StandIn::start(|request, _| {
+        let items: Vec<_> = (0..2).map(repo_json).collect();
+        assert!(request.query("q").is_some());
+        Response::json(serde_json::json!({ "total_count": 2, "items": items }).to_string())
+    });
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = run(&stand_in, dir.path(), &["--search", "language:rust", "-j", "out.json"]);
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let requests = stand_in.requests();
+    assert!(requests[0].path.starts_with("/search/repositories"));
+    assert_eq!(requests[0].query("q"), Some("language%3Arust"));
+    assert_eq!(names(dir.path()), ["repo-1", "repo-0"]);
+}
+
+#[test]
+fn repos_file_fetches_each_repo() {
+    let stand_in = StandIn::start(|request, _| {
+        let i: usize = request.path.trim_start_matches("/repos/owner/repo-").parse().unwrap();
+        Response::json(repo_json(i).to_string())
+    });
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("list.txt"), "owner/repo-4\n# skipped\nowner/repo-7\n").unwrap();
+
+    let output = run(&stand_in, dir.path(), &["--repos-file", "list.txt", "-j", "out.json"]);
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(paths(&stand_in), ["/repos/owner/repo-4", "/repos/owner/repo-7"]);
+    assert_eq!(names(dir.path()), ["repo-7", "repo-4"]);
+}
+
+#[test]
+fn missing_repo_in_file_exits_with_not_found() {
+    let stand_in = StandIn::start(|_, _| Response::status(404));
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("list.txt"), "owner/gone\n").unwrap();
+
+    let output = run(&stand_in, dir.path(), &["--repos-file", "list.txt", "-j", "out.json"]);
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("owner/gone"), "{}", stderr);
+    assert_eq!(output.status.code(), Some(3));
+}
+
+#[test]
+fn invalid_repos_file_is_rejected_before_fetching() {
+    let stand_in = StandIn::start(|request, base| paginated(request, base, 1));
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("list.txt"), "not a repo\n").unwrap();
+
+    let output = run(&stand_in, dir.path(), &["--repos-file", "list.txt", "-j", "out.json"]);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stand_in.requests().is_empty());
+}
+
+#[test]
+fn sources_are_cached_separately() {
+    let stand_in = StandIn::start(|request, base| paginated(request, base, 2));
+    let dir = tempfile::tempdir().unwrap();
+
+    for args in [["--org", "octocat"], ["--owned-by", "octocat"], ["-u", "octocat"]] {
+        let output = run(&stand_in, dir.path(), &[args[0], args[1], "-j", "out.json"]);
+        assert!(output.status.success());
+    }
+    assert_eq!(stand_in.requests().len(), 3);
+
+    let output = run(&stand_in, dir.path(), &["--org", "octocat", "-j", "out.json"]);
+    assert!(output.status.success());
+    assert_eq!(stand_in.requests().len(), 3);
+
+    let output = run(&stand_in, dir.path(), &["cache", "list"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("org:octocat"), "{}", stdout);
+    assert!(stdout.contains("owned:octocat"), "{}", stdout);
+}
+
+#[test]
+fn sources_conflict_with_user() {
+    let stand_in = StandIn::start(|request, base| paginated(request, base, 1));
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = run(&stand_in, dir.path(), &["-u", "octocat", "--org", "rust-lang"]);
+
+    assert!(!output.status.success());
+    assert!(stand_in.requests().is_empty());
+}
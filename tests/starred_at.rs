@@ -0,0 +1,74 @@
+mod common;
+
+use common::{repo_json, Response, StandIn};
+
+use github_most_popular::{format, Filter, Repo, Sort, StarredClient};
+
+use chrono::{TimeZone, Utc};
+
+fn star_json(i: usize, starred_at: &str) -> serde_json::Value {
+    serde_json::json!({ "starred_at": starred_at, "repo": repo_json(i) })
+}
+
+fn stand_in() -> StandIn {
+    StandIn::start(|_, _| {
+        let body = serde_json::json!([
+            star_json(1, "2022-10-20T08:00:00Z"),
+            star_json(2, "2021-03-01T12:30:00Z"),
+            star_json(3, "2022-06-15T00:00:00Z"),
+        ]);
+        Response::json(body.to_string())
+    })
+}
+
+#[test]
+fn captures_star_timestamps() {
+    let stand_in = stand_in();
+    let client = StarredClient::new()
+        .with_api_url(stand_in.url())
+        .with_token("test-token");
+
+    let repos = client.starred("octocat", None).unwrap();
+
+    assert_eq!(
+        stand_in.requests()[0].header("Accept"),
+        Some("application/vnd.github.star+json")
+    );
+    assert_eq!(
+        repos[1].starred_at,
+        Some(Utc.with_ymd_and_hms(2021, 3, 1, 12, 30, 0).unwrap())
+    );
+    assert_eq!(repos[1].name, "repo-2");
+}
+
+#[test]
+fn sorts_and_filters_by_star_date() {
+    let stand_in = stand_in();
+    let client = StarredClient::new()
+        .with_api_url(stand_in.url())
+        .with_token("test-token");
+    let mut repos = client.starred("octocat", None).unwrap();
+
+    Sort::parse("starred_at", None).unwrap().apply(&mut repos);
+    let names: Vec<_> = repos.iter().map(|repo| repo.name.as_str()).collect();
+    assert_eq!(names, ["repo-1", "repo-3", "repo-2"]);
+
+    let filter = Filter {
+        starred_after: Some(Utc.with_ymd_and_hms(2022, 1, 1, 0, 0, 0).unwrap()),
+        ..Filter::default()
+    };
+    assert_eq!(filter.apply(repos).len(), 2);
+}
+
+#[test]
+fn exports_keep_star_date() {
+    let stand_in = stand_in();
+    let client = StarredClient::new()
+        .with_api_url(stand_in.url())
+        .with_token("test-token");
+    let repos = client.starred("octocat", None).unwrap();
+
+    let json = format::to_json(&repos).unwrap();
+    let reimported: Vec<Repo> = serde_json::from_str(&json).unwrap();
+    assert_eq!(reimported, repos);
+}
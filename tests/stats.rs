@@ -0,0 +1,126 @@
+mod common;
+
+use common::{repo_json, run, Response, StandIn};
+
+use github_most_popular::stats::Count;
+use github_most_popular::{Repo, Stats};
+
+use chrono::{DateTime, Duration, Utc};
+
+use std::path::Path;
+use std::process::Output;
+
+fn now() -> DateTime<Utc> {
+    "2024-06-01T00:00:00Z".parse().unwrap()
+}
+
+/// repo i with the given language, license, topics and age in days
+fn repo(i: usize, language: Option<&str>, license: Option<&str>, topics: &[&str], age: i64) -> serde_json::Value {
+    let mut json = repo_json(i);
+    json["full_name"] = format!("owner-{}/repo-{}", i % 2, i).into();
+    json["owner"] = serde_json::json!({ "login": format!("owner-{}", i % 2) });
+    json["language"] = language.into();
+    json["license"] = license.map_or(serde_json::Value::Null, |key| {
+        serde_json::json!({ "key": key, "name": key, "spdx_id": key.to_uppercase() })
+    });
+    json["topics"] = topics.into();
+    json["created_at"] = (now() - Duration::days(age)).to_rfc3339().into();
+    json["pushed_at"] = (now() - Duration::days(i as i64)).to_rfc3339().into();
+    json["archived"] = (i == 4).into();
+    json
+}
+
+fn list() -> Vec<serde_json::Value> {
+    vec![
+        repo(1, Some("Rust"), Some("mit"), &["cli", "tui"], 100),
+        repo(2, Some("Rust"), Some("apache-2.0"), &["cli"], 500),
+        repo(3, Some("Go"), Some("mit"), &[], 1500),
+        repo(4, None, None, &["cli"], 5000),
+    ]
+}
+
+fn counts(counts: &[Count]) -> Vec<(&str, usize)> {
+    counts.iter().map(|count| (count.name.as_str(), count.count)).collect()
+}
+
+#[test]
+fn counts_languages_owners_licenses_and_topics() {
+    let repos: Vec<Repo> = list().into_iter().map(|json| serde_json::from_value(json).unwrap()).collect();
+
+    let stats = Stats::new(&repos, now());
+
+    assert_eq!(stats.repo_count, 4);
+    assert_eq!(stats.star_total, 10);
+    assert_eq!(stats.star_median, 2.5);
+    assert_eq!(counts(&stats.by_language), [("Rust", 2), ("Go", 1), ("none", 1)]);
+    assert_eq!(counts(&stats.by_owner), [("owner-0", 2), ("owner-1", 2)]);
+    assert_eq!(counts(&stats.by_license), [("MIT", 2), ("APACHE-2.0", 1), ("none", 1)]);
+    assert_eq!(counts(&stats.by_topic), [("cli", 3), ("tui", 1)]);
+    assert_eq!(
+        counts(&stats.by_age),
+        [
+            ("under 1 year", 1),
+            ("1 to 2 years", 1),
+            ("2 to 5 years", 1),
+            ("5 to 10 years", 0),
+            ("10 years or more", 1),
+        ]
+    );
+    assert_eq!(stats.archived, 1);
+    assert_eq!(stats.archived_share, 0.25);
+    let pushed: Vec<_> = stats.recently_pushed.iter().map(|repo| repo.full_name.as_str()).collect();
+    assert_eq!(pushed, ["owner-1/repo-1", "owner-0/repo-2", "owner-1/repo-3", "owner-0/repo-4"]);
+}
+
+#[test]
+fn empty_list_has_zero_stats() {
+    let mut undated = repo_json(7);
+    undated["created_at"] = serde_json::Value::Null;
+    let repo: Repo = serde_json::from_value(undated).unwrap();
+
+    let empty = Stats::new(&[], now());
+    let unknown = Stats::new(&[repo], now());
+
+    assert_eq!(empty.star_median, 0.0);
+    assert_eq!(empty.archived_share, 0.0);
+    assert!(empty.by_language.is_empty());
+    assert!(empty.recently_pushed.is_empty());
+    assert_eq!(unknown.star_median, 7.0);
+    assert_eq!(unknown.by_age.last(), Some(&Count { name: "unknown".to_string(), count: 1 }));
+}
+
+/// run the `stats` subcommand with args
+fn run_stats(stand_in: &StandIn, dir: &Path, args: &[&str]) -> Output {
+    run(stand_in, dir, &[&["stats"], args].concat())
+}
+
+#[test]
+fn stats_command_prints_tables() {
+    let stand_in = StandIn::start(|_, _| Response::json(serde_json::to_string(&list()).unwrap()));
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = run_stats(&stand_in, dir.path(), &["-u", "octocat"]);
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stand_in.requests()[0].path.starts_with("/users/octocat/starred"));
+    assert!(stdout.contains("Stars:       10 total, 2.5 median"), "{}", stdout);
+    assert!(stdout.contains("Archived:    1 (25.0%)"), "{}", stdout);
+    assert!(stdout.contains("  Rust      2   50.0%"), "{}", stdout);
+    assert!(stdout.contains("  cli      3   75.0%"), "{}", stdout);
+    assert!(stdout.contains("  owner-1/repo-1  "), "{}", stdout);
+}
+
+#[test]
+fn stats_command_writes_json() {
+    let stand_in = StandIn::start(|_, _| Response::json(serde_json::to_string(&list()).unwrap()));
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = run_stats(&stand_in, dir.path(), &["-u", "octocat", "-j", "-"]);
+
+    let stats: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(stats["repo_count"], 4);
+    assert_eq!(stats["by_language"][0], serde_json::json!({ "name": "Rust", "count": 2 }));
+    assert_eq!(stats["by_topic"].as_array().unwrap().len(), 2);
+    assert_eq!(stats["recently_pushed"][0]["url"], "https://github.com/owner/repo-1");
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use common::starred_fixture;
+
+use github_most_popular::format::{self, ExportMeta};
+use github_most_popular::Source;
+
+use chrono::{TimeZone, Utc};
+
+#[test]
+fn writes_named_array_of_tables() {
+    let fetched_at = Utc.with_ymd_and_hms(2022, 10, 25, 6, 52, 35).unwrap();
+    let toml = format::to_toml("hlissner", fetched_at, &starred_fixture()).unwrap();
+
+    assert!(toml.starts_with("[meta]\n"));
+    assert_eq!(toml.matches("\n[[repos]]\n").count(), 10);
+    assert!(!toml.contains("[[]]"));
+}
+
+#[test]
+fn round_trips_through_toml() {
+    let repos = starred_fixture();
+    let fetched_at = Utc.with_ymd_and_hms(2022, 10, 25, 6, 52, 35).unwrap();
+
+    let toml = format::to_toml("hlissner", fetched_at, &repos).unwrap();
+    let export = format::from_toml(&toml).unwrap();
+
+    assert_eq!(
+        export.meta,
+        ExportMeta {
+            user: Some("hlissner".into()),
+            source: None,
+            fetched_at,
+            count: 10,
+        }
+    );
+    assert_eq!(export.repos, repos);
+}
+
+#[test]
+fn parses_with_generic_toml_reader() {
+    let toml = format::to_toml("hlissner", Utc::now(), &starred_fixture()).unwrap();
+
+    let value: toml::Value = toml::from_str(&toml).unwrap();
+    assert_eq!(value["meta"]["count"].as_integer(), Some(10));
+    assert_eq!(value["repos"][0]["owner"]["login"].as_str(), Some("progfolio"));
+}
+
+#[test]
+fn other_sources_are_recorded_instead_of_a_user() {
+    let toml = format::to_toml_for(&Source::Org("rust-lang".into()), Utc::now(), &starred_fixture()).unwrap();
+
+    let export = format::from_toml(&toml).unwrap();
+    assert_eq!(export.meta.user, None);
+    assert_eq!(export.meta.source.as_deref(), Some("org:rust-lang"));
+    assert!(!toml.contains("user ="));
+}